@@ -0,0 +1,291 @@
+// Repeat and heavy actor rules
+//
+// A wallet's transactions are counted over named windows (5m, 1h, 24h, 7d,
+// ...). Each window can flag the wallet once it makes enough transactions in
+// it, and report coordinated activity once its volume there gets big enough.
+// Windows come from the config file's `actor_windows` or `--actor-window`.
+
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// A window length such as "90s", "5m", "1h", "24h", "7d" or "2w". The text
+/// it was written as doubles as its name.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct WindowLength {
+    name: String,
+    secs: i64,
+}
+
+impl WindowLength {
+    pub fn secs(&self) -> i64 {
+        self.secs
+    }
+}
+
+impl FromStr for WindowLength {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        let split = name
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(name.len());
+        let (count, unit) = name.split_at(split);
+
+        let count: i64 = count
+            .parse()
+            .map_err(|_| format!("invalid window '{}', expected e.g. 5m, 1h or 7d", s))?;
+        let unit_secs = match unit {
+            "s" => 1,
+            "m" => 60,
+            "h" => 3600,
+            "d" => 86400,
+            "w" => 7 * 86400,
+            _ => {
+                return Err(format!(
+                    "invalid window unit in '{}', expected s, m, h, d or w",
+                    s
+                ))
+            }
+        };
+        if count == 0 {
+            return Err(format!("window '{}' is empty", s));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            secs: count.saturating_mul(unit_secs),
+        })
+    }
+}
+
+impl TryFrom<String> for WindowLength {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<WindowLength> for String {
+    fn from(window: WindowLength) -> Self {
+        window.name
+    }
+}
+
+impl fmt::Display for WindowLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+/// What a wallet becomes when a window's transaction count is reached.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActorLevel {
+    #[default]
+    Repeat,
+    Heavy,
+}
+
+impl fmt::Display for ActorLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActorLevel::Repeat => write!(f, "repeat"),
+            ActorLevel::Heavy => write!(f, "heavy"),
+        }
+    }
+}
+
+/// One window and its triggers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActorWindow {
+    pub window: WindowLength,
+    /// Transactions in the window that make the wallet a repeat or heavy actor
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transactions: Option<usize>,
+    /// Dollar volume in the window reported as coordinated activity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volume: Option<Decimal>,
+    /// What reaching `transactions` makes the wallet
+    #[serde(default)]
+    pub level: ActorLevel,
+}
+
+/// The rules used when none are configured: a repeat actor makes 2
+/// transactions in an hour, a heavy actor 5 in a day, and $200,000 in an
+/// hour is coordinated activity.
+pub fn default_windows() -> Vec<ActorWindow> {
+    vec![
+        ActorWindow {
+            window: "1h".parse().expect("valid window"),
+            transactions: Some(2),
+            volume: Some(dec!(200000)),
+            level: ActorLevel::Repeat,
+        },
+        ActorWindow {
+            window: "24h".parse().expect("valid window"),
+            transactions: Some(5),
+            volume: None,
+            level: ActorLevel::Heavy,
+        },
+    ]
+}
+
+/// The windows in effect: the command line's, else the config file's, else
+/// the defaults.
+pub fn pick(cli: Vec<ActorWindow>, config: &[ActorWindow]) -> Vec<ActorWindow> {
+    if !cli.is_empty() {
+        cli
+    } else if !config.is_empty() {
+        config.to_vec()
+    } else {
+        default_windows()
+    }
+}
+
+/// Parsed from `--actor-window`, e.g. `24h,txns=5,level=heavy` or
+/// `5m,txns=3,volume=50000`.
+impl FromStr for ActorWindow {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(',');
+        let mut window = ActorWindow {
+            window: parts.next().unwrap_or_default().parse()?,
+            transactions: None,
+            volume: None,
+            level: ActorLevel::default(),
+        };
+
+        for part in parts {
+            let (key, value) = part
+                .split_once('=')
+                .ok_or_else(|| format!("expected key=value, got '{}'", part))?;
+            let value = value.trim();
+            match key.trim() {
+                "txns" | "transactions" => {
+                    let count: usize = value
+                        .parse()
+                        .map_err(|_| format!("invalid transaction count '{}'", value))?;
+                    if count == 0 {
+                        return Err("transaction count must be at least 1".to_string());
+                    }
+                    window.transactions = Some(count);
+                }
+                "volume" => {
+                    window.volume = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid volume '{}'", value))?,
+                    );
+                }
+                "level" => {
+                    window.level = match value {
+                        "repeat" => ActorLevel::Repeat,
+                        "heavy" => ActorLevel::Heavy,
+                        _ => {
+                            return Err(format!(
+                                "invalid level '{}', expected repeat or heavy",
+                                value
+                            ))
+                        }
+                    };
+                }
+                other => {
+                    return Err(format!(
+                        "unknown setting '{}', expected txns, volume or level",
+                        other
+                    ))
+                }
+            }
+        }
+
+        Ok(window)
+    }
+}
+
+/// Written back in `--actor-window` form.
+impl fmt::Display for ActorWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.window)?;
+        if let Some(count) = self.transactions {
+            write!(f, ",txns={}", count)?;
+            if self.level != ActorLevel::Repeat {
+                write!(f, ",level={}", self.level)?;
+            }
+        }
+        if let Some(volume) = self.volume {
+            write!(f, ",volume={}", volume)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(spec: &str) -> ActorWindow {
+        spec.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_actor_windows() {
+        let heavy = window("24h,txns=5,level=heavy");
+        assert_eq!(heavy.window.secs(), 86400);
+        assert_eq!(heavy.transactions, Some(5));
+        assert_eq!(heavy.volume, None);
+        assert_eq!(heavy.level, ActorLevel::Heavy);
+
+        let burst = window("5m, transactions=3, volume=50000");
+        assert_eq!(burst.window.secs(), 300);
+        assert_eq!(burst.transactions, Some(3));
+        assert_eq!(burst.volume, Some(dec!(50000)));
+        assert_eq!(burst.level, ActorLevel::Repeat);
+
+        assert_eq!(window("2w").window.secs(), 14 * 86400);
+        // Written back the way it was given
+        assert_eq!(heavy.to_string(), "24h,txns=5,level=heavy");
+        assert_eq!(window(&burst.to_string()), burst);
+    }
+
+    #[test]
+    fn rejects_bad_actor_windows() {
+        for spec in [
+            "",
+            "h",
+            "0m",
+            "5y",
+            "1h,txns",
+            "1h,txns=0",
+            "1h,txns=many",
+            "1h,volume=lots",
+            "1h,level=whale",
+            "1h,size=5",
+        ] {
+            assert!(spec.parse::<ActorWindow>().is_err(), "accepted '{}'", spec);
+        }
+    }
+
+    #[test]
+    fn default_windows_flag_repeat_and_heavy_actors() {
+        let windows = default_windows();
+        let specs: Vec<String> = windows.iter().map(ToString::to_string).collect();
+        assert_eq!(specs, ["1h,txns=2,volume=200000", "24h,txns=5,level=heavy"]);
+    }
+
+    #[test]
+    fn command_line_windows_win_over_the_config() {
+        let cli = vec![window("5m,txns=3,level=heavy")];
+        let config = vec![window("1h,txns=10")];
+
+        assert_eq!(pick(cli.clone(), &config), cli);
+        assert_eq!(pick(Vec::new(), &config), config);
+        assert_eq!(pick(Vec::new(), &[]), default_windows());
+    }
+}
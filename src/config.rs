@@ -1,3 +1,6 @@
+use crate::actors::ActorWindow;
+use crate::watchlist::{WatchedMarket, WatchedWallet};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
@@ -7,15 +10,171 @@ pub struct Config {
     pub kalshi_api_key_id: Option<String>,
     pub kalshi_private_key: Option<String>,
     pub webhook_url: Option<String>,
+    /// Kalshi environment whose default URLs are used (prod or demo)
+    pub kalshi_env: Option<KalshiEnv>,
+    /// Base URL overrides, e.g. for a proxy or a local mock server
+    pub kalshi_api_url: Option<String>,
+    pub kalshi_ws_url: Option<String>,
+    pub polymarket_data_api_url: Option<String>,
+    pub polymarket_gamma_api_url: Option<String>,
+    pub polymarket_ws_url: Option<String>,
+    pub manifold_api_url: Option<String>,
+    /// HTTP timeout, retry and rate budget settings
+    pub http_timeout_secs: Option<u64>,
+    pub http_max_retries: Option<u32>,
+    pub kalshi_requests_per_minute: Option<u32>,
+    pub polymarket_requests_per_minute: Option<u32>,
+    pub manifold_requests_per_minute: Option<u32>,
+    /// Platforms to watch (Polymarket and Kalshi by default) and how often to poll each
+    pub polymarket_enabled: Option<bool>,
+    pub kalshi_enabled: Option<bool>,
+    pub manifold_enabled: Option<bool>,
+    pub polymarket_interval_secs: Option<u64>,
+    pub kalshi_interval_secs: Option<u64>,
+    pub manifold_interval_secs: Option<u64>,
+    /// Dollar value of one mana when comparing Manifold bets to the threshold
+    pub manifold_mana_usd_rate: Option<Decimal>,
+    /// How long market details are reused before being fetched again
+    pub market_cache_ttl_secs: Option<u64>,
+    /// Consecutive empty or failed polls before a feed is reported degraded
+    pub feed_degraded_after_polls: Option<u32>,
+    /// Markets followed with their own thresholds
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watchlist: Vec<WatchedMarket>,
+    /// Wallets whose trades always alert, managed with `wwatcher wallets`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wallets: Vec<WatchedWallet>,
+    /// Repeat and heavy actor windows (default: 1h and 24h)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub actor_windows: Vec<ActorWindow>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum KalshiEnv {
+    #[default]
+    Prod,
+    Demo,
+}
+
+impl KalshiEnv {
+    pub fn api_url(self) -> &'static str {
+        match self {
+            KalshiEnv::Prod => "https://api.elections.kalshi.com/trade-api/v2",
+            KalshiEnv::Demo => "https://demo-api.kalshi.co/trade-api/v2",
+        }
+    }
+
+    pub fn ws_url(self) -> &'static str {
+        match self {
+            KalshiEnv::Prod => "wss://api.elections.kalshi.com/trade-api/ws/v2",
+            KalshiEnv::Demo => "wss://demo-api.kalshi.co/trade-api/ws/v2",
+        }
+    }
+}
+
+impl std::fmt::Display for KalshiEnv {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KalshiEnv::Prod => write!(f, "prod"),
+            KalshiEnv::Demo => write!(f, "demo"),
+        }
+    }
+}
+
+pub const DEFAULT_POLYMARKET_DATA_API_URL: &str = "https://data-api.polymarket.com";
+pub const DEFAULT_POLYMARKET_GAMMA_API_URL: &str = "https://gamma-api.polymarket.com";
+pub const DEFAULT_POLYMARKET_WS_URL: &str = "wss://ws-subscriptions-clob.polymarket.com/ws/market";
+pub const DEFAULT_MANIFOLD_API_URL: &str = "https://api.manifold.markets/v0";
+
+/// Endpoint settings given on the command line or through the environment.
+/// These take precedence over the config file.
+#[derive(Debug, Clone, Default)]
+pub struct EndpointOverrides {
+    pub kalshi_env: Option<KalshiEnv>,
+    pub kalshi_api_url: Option<String>,
+    pub kalshi_ws_url: Option<String>,
+    pub polymarket_data_api_url: Option<String>,
+    pub polymarket_gamma_api_url: Option<String>,
+    pub polymarket_ws_url: Option<String>,
+    pub manifold_api_url: Option<String>,
+}
+
+/// The base URLs in effect for this run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Endpoints {
+    pub kalshi_env: KalshiEnv,
+    pub kalshi_api: String,
+    pub kalshi_ws: String,
+    pub polymarket_data_api: String,
+    pub polymarket_gamma_api: String,
+    pub polymarket_ws: String,
+    pub manifold_api: String,
+}
+
+impl Endpoints {
+    pub fn resolve(config: &Config, overrides: &EndpointOverrides) -> Self {
+        let kalshi_env = overrides
+            .kalshi_env
+            .or(config.kalshi_env)
+            .unwrap_or_default();
+
+        let pick = |cli: &Option<String>, file: &Option<String>, default: &str| {
+            cli.clone()
+                .or_else(|| file.clone())
+                .unwrap_or_else(|| default.to_string())
+                .trim_end_matches('/')
+                .to_string()
+        };
+
+        Self {
+            kalshi_env,
+            kalshi_api: pick(
+                &overrides.kalshi_api_url,
+                &config.kalshi_api_url,
+                kalshi_env.api_url(),
+            ),
+            kalshi_ws: pick(
+                &overrides.kalshi_ws_url,
+                &config.kalshi_ws_url,
+                kalshi_env.ws_url(),
+            ),
+            polymarket_data_api: pick(
+                &overrides.polymarket_data_api_url,
+                &config.polymarket_data_api_url,
+                DEFAULT_POLYMARKET_DATA_API_URL,
+            ),
+            polymarket_gamma_api: pick(
+                &overrides.polymarket_gamma_api_url,
+                &config.polymarket_gamma_api_url,
+                DEFAULT_POLYMARKET_GAMMA_API_URL,
+            ),
+            polymarket_ws: pick(
+                &overrides.polymarket_ws_url,
+                &config.polymarket_ws_url,
+                DEFAULT_POLYMARKET_WS_URL,
+            ),
+            manifold_api: pick(
+                &overrides.manifold_api_url,
+                &config.manifold_api_url,
+                DEFAULT_MANIFOLD_API_URL,
+            ),
+        }
+    }
 }
 
 fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    data_path("config.json")
+}
+
+/// Path of a file kept in the wwatcher config directory (history, caches, state).
+pub fn data_path(file_name: &str) -> Result<PathBuf, Box<dyn std::error::Error>> {
     let config_dir = dirs::config_dir().ok_or("Could not determine config directory")?;
 
     let app_config_dir = config_dir.join("wwatcher");
     fs::create_dir_all(&app_config_dir)?;
 
-    Ok(app_config_dir.join("config.json"))
+    Ok(app_config_dir.join(file_name))
 }
 
 pub fn save_config(config: &Config) -> Result<(), Box<dyn std::error::Error>> {
@@ -0,0 +1,417 @@
+// Shared HTTP layer
+//
+// One pooled reqwest client for the whole process, with timeouts, jittered
+// exponential backoff, `Retry-After` handling on 429/5xx and a request budget
+// per platform. Responses can be recorded, or served from a recording.
+
+use crate::config::Config;
+use crate::recording::{Player, RecordedResponse, Recorder};
+use rand::Rng;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone)]
+pub struct HttpSettings {
+    pub timeout: Duration,
+    pub max_retries: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub kalshi_requests_per_minute: u32,
+    pub polymarket_requests_per_minute: u32,
+    pub manifold_requests_per_minute: u32,
+}
+
+impl HttpSettings {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            timeout: Duration::from_secs(config.http_timeout_secs.unwrap_or(10)),
+            max_retries: config.http_max_retries.unwrap_or(3),
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            kalshi_requests_per_minute: config.kalshi_requests_per_minute.unwrap_or(600),
+            polymarket_requests_per_minute: config.polymarket_requests_per_minute.unwrap_or(600),
+            // Manifold allows 500 requests per minute per IP
+            manifold_requests_per_minute: config.manifold_requests_per_minute.unwrap_or(500),
+        }
+    }
+}
+
+/// Where responses are copied to, or served from instead of the network.
+#[derive(Clone)]
+pub enum Tap {
+    Record(Arc<Recorder>),
+    Replay(Arc<Player>),
+}
+
+/// A cloneable handle to the shared client. Clones share the connection pool;
+/// each handle made with `with_budget` gets its own request budget.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: reqwest::Client,
+    settings: Arc<HttpSettings>,
+    budget: Option<Arc<RateBudget>>,
+    tap: Option<Tap>,
+}
+
+impl HttpClient {
+    pub fn new(settings: HttpSettings) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(settings.timeout)
+            .build()
+            .expect("failed to build HTTP client");
+
+        Self {
+            client,
+            settings: Arc::new(settings),
+            budget: None,
+            tap: None,
+        }
+    }
+
+    /// A client for delivering webhooks.
+    ///
+    /// For self-hosted instances with self-signed certs, accept invalid certs.
+    pub fn webhook(settings: &HttpSettings) -> Self {
+        let client = reqwest::Client::builder()
+            .danger_accept_invalid_certs(true)
+            .timeout(Duration::from_secs(5))
+            .build()
+            .expect("failed to build webhook client");
+
+        Self {
+            client,
+            settings: Arc::new(settings.clone()),
+            budget: None,
+            tap: None,
+        }
+    }
+
+    /// Record every response, or replay a recording instead of sending
+    /// requests. Handles made from this one with `with_budget` share the tap.
+    pub fn with_tap(mut self, tap: Tap) -> Self {
+        self.tap = Some(tap);
+        self
+    }
+
+    /// A handle sharing this client's pool, limited to `requests_per_minute`.
+    pub fn with_budget(&self, requests_per_minute: u32) -> Self {
+        Self {
+            client: self.client.clone(),
+            settings: self.settings.clone(),
+            budget: Some(Arc::new(RateBudget::new(requests_per_minute))),
+            tap: self.tap.clone(),
+        }
+    }
+
+    /// Send a request, retrying transient failures.
+    ///
+    /// `build` is called for every attempt so signed requests get a fresh
+    /// signature. Connection errors, timeouts, 429 and 5xx responses are
+    /// retried up to `max_retries` times; the last response is returned as-is
+    /// so callers can report its status. A response asking to wait longer
+    /// than `max_backoff` is returned rather than retried early.
+    pub async fn send<F>(&self, build: F) -> Result<Response, reqwest::Error>
+    where
+        F: Fn(&reqwest::Client) -> RequestBuilder,
+    {
+        self.send_retrying(build, is_transient).await
+    }
+
+    /// Send a request that must not arrive twice, like a webhook POST.
+    ///
+    /// Like `send`, but only errors from before anything was sent are
+    /// retried. A timeout or a failure mid-request may have been delivered.
+    pub async fn deliver<F>(&self, build: F) -> Result<Response, reqwest::Error>
+    where
+        F: Fn(&reqwest::Client) -> RequestBuilder,
+    {
+        self.send_retrying(build, reqwest::Error::is_connect).await
+    }
+
+    async fn send_retrying<F>(
+        &self,
+        build: F,
+        retry_error: fn(&reqwest::Error) -> bool,
+    ) -> Result<Response, reqwest::Error>
+    where
+        F: Fn(&reqwest::Client) -> RequestBuilder,
+    {
+        // A replayed response is final: retries were already made when recording
+        if let Some(Tap::Replay(player)) = &self.tap {
+            let request = build(&self.client).build()?;
+            return Ok(replayed(player.next(request.url().as_str()).await));
+        }
+
+        let mut attempt = 0;
+
+        loop {
+            if let Some(budget) = &self.budget {
+                budget.acquire().await;
+            }
+
+            let result = build(&self.client).send().await;
+            let retries_left = attempt < self.settings.max_retries;
+
+            let delay = match &result {
+                Ok(response) if is_retryable_status(response.status()) && retries_left => {
+                    match retry_after(response) {
+                        // Retrying sooner than asked would only be refused again
+                        Some(wait) if wait > self.settings.max_backoff => None,
+                        Some(wait) => Some(wait),
+                        None => Some(self.backoff(attempt)),
+                    }
+                }
+                Err(e) if retry_error(e) && retries_left => Some(self.backoff(attempt)),
+                _ => None,
+            };
+
+            let Some(delay) = delay else {
+                return match (&self.tap, result) {
+                    (Some(Tap::Record(recorder)), Ok(response)) => record(recorder, response).await,
+                    (_, result) => result,
+                };
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    // Full jitter: a random delay between zero and the exponential ceiling
+    fn backoff(&self, attempt: u32) -> Duration {
+        let ceiling = self
+            .settings
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.settings.max_backoff);
+        let millis = ceiling.as_millis().max(1) as u64;
+        Duration::from_millis(rand::thread_rng().gen_range(0..=millis))
+    }
+}
+
+// Save a response's body and hand the caller an identical response
+async fn record(recorder: &Recorder, response: Response) -> Result<Response, reqwest::Error> {
+    let url = response.url().to_string();
+    let status = response.status();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+
+    recorder.record(&RecordedResponse {
+        at: chrono::Utc::now(),
+        url,
+        status: status.as_u16(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    });
+
+    let mut rebuilt = ::http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.headers_mut() = headers;
+    Ok(Response::from(rebuilt))
+}
+
+// A response from the recording, or 404 when it has none left for the URL
+fn replayed(recorded: Option<RecordedResponse>) -> Response {
+    let (status, body) = match recorded {
+        Some(recorded) => (
+            StatusCode::from_u16(recorded.status).unwrap_or(StatusCode::OK),
+            recorded.body,
+        ),
+        None => (StatusCode::NOT_FOUND, "not in the recording".to_string()),
+    };
+
+    let mut response = ::http::Response::new(body);
+    *response.status_mut() = status;
+    Response::from(response)
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn is_transient(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+// Retry-After is either a number of seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?;
+
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let at = chrono::DateTime::parse_from_rfc2822(value.trim()).ok()?;
+    let wait = at.signed_duration_since(chrono::Utc::now());
+    Some(wait.to_std().unwrap_or_default())
+}
+
+/// Token bucket allowing a burst of up to one second's worth of requests.
+struct RateBudget {
+    per_second: f64,
+    capacity: f64,
+    state: Mutex<(f64, Instant)>,
+}
+
+impl RateBudget {
+    fn new(requests_per_minute: u32) -> Self {
+        let per_second = f64::from(requests_per_minute.max(1)) / 60.0;
+        let capacity = per_second.max(1.0);
+        Self {
+            per_second,
+            capacity,
+            state: Mutex::new((capacity, Instant::now())),
+        }
+    }
+
+    async fn acquire(&self) {
+        loop {
+            let wait = {
+                let mut state = self.state.lock().await;
+                let (tokens, last) = &mut *state;
+
+                let now = Instant::now();
+                *tokens = (*tokens + now.duration_since(*last).as_secs_f64() * self.per_second)
+                    .min(self.capacity);
+                *last = now;
+
+                if *tokens >= 1.0 {
+                    *tokens -= 1.0;
+                    return;
+                }
+                Duration::from_secs_f64((1.0 - *tokens) / self.per_second)
+            };
+
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    type Arrivals = Arc<std::sync::Mutex<Vec<Instant>>>;
+
+    // Answers the nth request with the nth status line and headers, then
+    // "200 OK" once they run out. An empty entry never answers.
+    async fn serve(responses: Vec<&'static str>) -> (String, Arrivals) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let arrivals = Arrivals::default();
+        let seen = arrivals.clone();
+        tokio::spawn(async move {
+            let mut responses = responses.into_iter();
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                seen.lock().unwrap().push(Instant::now());
+
+                let response = responses.next().unwrap_or("200 OK");
+                if response.is_empty() {
+                    tokio::spawn(async move {
+                        tokio::time::sleep(Duration::from_secs(60)).await;
+                        drop(socket);
+                    });
+                    continue;
+                }
+                let response = format!(
+                    "HTTP/1.1 {}\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok",
+                    response
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        (url, arrivals)
+    }
+
+    fn client() -> HttpClient {
+        HttpClient::new(HttpSettings {
+            timeout: Duration::from_millis(500),
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(10),
+            max_backoff: Duration::from_secs(5),
+            ..HttpSettings::from_config(&Config::default())
+        })
+    }
+
+    fn count(arrivals: &Arrivals) -> usize {
+        arrivals.lock().unwrap().len()
+    }
+
+    #[tokio::test]
+    async fn retries_a_server_error() {
+        let (url, arrivals) = serve(vec!["503 Service Unavailable"]).await;
+        let response = client().send(|c| c.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(count(&arrivals), 2);
+    }
+
+    #[tokio::test]
+    async fn waits_as_long_as_retry_after_asks() {
+        let (url, arrivals) = serve(vec!["429 Too Many Requests\r\nRetry-After: 1"]).await;
+        let response = client().send(|c| c.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let arrivals = arrivals.lock().unwrap();
+        assert_eq!(arrivals.len(), 2);
+        assert!(arrivals[1] - arrivals[0] >= Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn gives_up_when_retry_after_is_too_long() {
+        let (url, arrivals) = serve(vec!["429 Too Many Requests\r\nRetry-After: 120"]).await;
+        let response = client().send(|c| c.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(count(&arrivals), 1);
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        let (url, arrivals) = serve(vec!["404 Not Found"]).await;
+        let response = client().send(|c| c.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(count(&arrivals), 1);
+    }
+
+    #[tokio::test]
+    async fn timeouts_are_retried_but_not_redelivered() {
+        let (url, arrivals) = serve(vec!["", ""]).await;
+        let response = client().send(|c| c.get(&url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(count(&arrivals), 3);
+
+        let (url, arrivals) = serve(vec![""]).await;
+        let error = client().deliver(|c| c.post(&url)).await.unwrap_err();
+        assert!(error.is_timeout());
+        assert_eq!(count(&arrivals), 1);
+    }
+
+    #[tokio::test]
+    async fn budget_spaces_out_requests() {
+        let (url, arrivals) = serve(Vec::new()).await;
+        // One request a second, with a burst of one
+        let http = client().with_budget(60);
+        for _ in 0..2 {
+            http.send(|c| c.get(&url)).await.unwrap();
+        }
+
+        let arrivals = arrivals.lock().unwrap();
+        assert!(arrivals[1] - arrivals[0] >= Duration::from_millis(900));
+    }
+}
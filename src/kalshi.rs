@@ -1,6 +1,27 @@
 use crate::config::Config;
+use crate::http::HttpClient;
+use crate::market_cache::MarketCache;
+use crate::source::{self, SourceError, TradeSource};
+use crate::types::{
+    self, MarketInfo, NormalizedTrade, ParsedPage, Platform, Side, StreamEvent, TradeBatch,
+    TradeKind,
+};
+use crate::watchlist::KalshiScope;
+use base64::Engine;
+use colored::Colorize;
+use rsa::pkcs1::DecodeRsaPrivateKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::pss::BlindedSigningKey;
+use rsa::sha2::Sha256;
+use rsa::signature::{RandomizedSigner, SignatureEncoding};
+use rsa::RsaPrivateKey;
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
 use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use thiserror::Error;
+use tokio::sync::mpsc;
 
 #[derive(Error, Debug)]
 pub enum KalshiError {
@@ -8,114 +29,653 @@ pub enum KalshiError {
     RequestFailed(#[from] reqwest::Error),
     #[error("Failed to parse response: {0}")]
     ParseError(String),
+    #[error("Authentication error: {0}")]
+    AuthError(String),
+    #[error("Unexpected response format: {message} (payload: {sample})")]
+    SchemaError { message: String, sample: String },
 }
 
+/// Signs Kalshi API requests with the account's RSA private key.
+///
+/// Every request carries `KALSHI-ACCESS-KEY`, `KALSHI-ACCESS-TIMESTAMP` and
+/// `KALSHI-ACCESS-SIGNATURE`, where the signature is RSA-PSS/SHA256 over
+/// `timestamp + METHOD + path` (path without query string).
+pub struct KalshiSigner {
+    key_id: String,
+    signing_key: BlindedSigningKey<Sha256>,
+}
+
+impl KalshiSigner {
+    pub fn new(key_id: &str, private_key_pem: &str) -> Result<Self, KalshiError> {
+        let pem = private_key_pem.trim();
+        let private_key = RsaPrivateKey::from_pkcs8_pem(pem)
+            .or_else(|_| RsaPrivateKey::from_pkcs1_pem(pem))
+            .map_err(|e| KalshiError::AuthError(format!("Invalid private key: {}", e)))?;
+
+        Ok(Self {
+            key_id: key_id.to_string(),
+            signing_key: BlindedSigningKey::<Sha256>::new(private_key),
+        })
+    }
+
+    /// Build a signer from the saved configuration.
+    ///
+    /// `kalshi_private_key` may hold either the PEM contents or a path to the
+    /// PEM file. Returns `Ok(None)` when no credentials are configured.
+    pub fn from_config(config: &Config) -> Result<Option<Self>, KalshiError> {
+        let (Some(key_id), Some(private_key)) =
+            (&config.kalshi_api_key_id, &config.kalshi_private_key)
+        else {
+            return Ok(None);
+        };
+
+        let pem = if private_key.contains("-----BEGIN") {
+            private_key.clone()
+        } else {
+            let path = expand_home(private_key.trim());
+            std::fs::read_to_string(&path).map_err(|e| {
+                KalshiError::AuthError(format!(
+                    "Could not read private key file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?
+        };
+
+        Self::new(key_id, &pem).map(Some)
+    }
+
+    pub fn sign(&self, timestamp_ms: i64, method: &str, path: &str) -> String {
+        let message = format!("{}{}{}", timestamp_ms, method.to_uppercase(), path);
+        let mut rng = rand::thread_rng();
+        let signature = self.signing_key.sign_with_rng(&mut rng, message.as_bytes());
+        base64::engine::general_purpose::STANDARD.encode(signature.to_bytes())
+    }
+
+    /// The authentication headers for a request made now.
+    pub fn headers(&self, method: &str, path: &str) -> [(&'static str, String); 3] {
+        let timestamp_ms = chrono::Utc::now().timestamp_millis();
+        let signature = self.sign(timestamp_ms, method, path);
+
+        [
+            ("KALSHI-ACCESS-KEY", self.key_id.clone()),
+            ("KALSHI-ACCESS-TIMESTAMP", timestamp_ms.to_string()),
+            ("KALSHI-ACCESS-SIGNATURE", signature),
+        ]
+    }
+
+    /// Attach the Kalshi authentication headers to a request.
+    pub fn apply(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        method: &str,
+        path: &str,
+    ) -> reqwest::RequestBuilder {
+        for (name, value) in self.headers(method, path) {
+            request = request.header(name, value);
+        }
+        request
+    }
+}
+
+fn expand_home(path: &str) -> std::path::PathBuf {
+    if let Some(rest) = path.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+    std::path::PathBuf::from(path)
+}
+
+/// Issue a GET against the Kalshi API, signing it when credentials are available.
+///
+/// `api_url` is the API base including its path prefix, e.g.
+/// `https://api.elections.kalshi.com/trade-api/v2`.
+async fn get(
+    http: &HttpClient,
+    api_url: &str,
+    signer: Option<&KalshiSigner>,
+    endpoint: &str,
+    query: &[(&str, &str)],
+) -> Result<String, KalshiError> {
+    let url = reqwest::Url::parse(&format!("{}{}", api_url, endpoint))
+        .map_err(|e| KalshiError::ParseError(format!("Invalid API URL: {}", e)))?;
+
+    // The signature covers the full path, including the API prefix
+    let path = url.path().to_string();
+
+    let response = http
+        .send(|client| {
+            let request = client
+                .get(url.clone())
+                .query(query)
+                .header("Accept", "application/json");
+
+            match signer {
+                Some(signer) => signer.apply(request, "GET", &path),
+                None => request,
+            }
+        })
+        .await?;
+    let status = response.status();
+
+    if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+        return Err(KalshiError::AuthError(format!(
+            "API rejected credentials (status: {})",
+            status
+        )));
+    }
+
+    if !status.is_success() {
+        return Err(KalshiError::ParseError(format!(
+            "API returned status: {}",
+            status
+        )));
+    }
+
+    Ok(response.text().await?)
+}
+
+/// A public fill.
+///
+/// Kalshi sends prices both in cents (`yes_price`) and as fixed-point dollar
+/// strings (`yes_price_dollars`), and the contract count both whole (`count`)
+/// and fixed-point (`count_fp`), which can be fractional. Either form is
+/// accepted; the fixed-point one wins when both are present.
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Trade {
     #[serde(rename = "trade_id")]
     pub trade_id: String,
     #[serde(rename = "ticker")]
     pub ticker: String,
-    #[serde(rename = "price")]
-    pub price: f64,
-    #[serde(rename = "count")]
-    pub count: i32,
-    #[serde(rename = "yes_price")]
-    pub yes_price: f64,
-    #[serde(rename = "no_price")]
-    pub no_price: f64,
+    #[serde(rename = "count", default)]
+    pub count: Option<Decimal>,
+    #[serde(rename = "count_fp", default)]
+    pub count_fp: Option<Decimal>,
+    /// In cents
+    #[serde(rename = "yes_price", default)]
+    pub yes_price: Option<Decimal>,
+    /// In cents
+    #[serde(rename = "no_price", default)]
+    pub no_price: Option<Decimal>,
+    #[serde(rename = "yes_price_dollars", default)]
+    pub yes_price_dollars: Option<Decimal>,
+    #[serde(rename = "no_price_dollars", default)]
+    pub no_price_dollars: Option<Decimal>,
     #[serde(rename = "taker_side")]
     pub taker_side: String,
     #[serde(rename = "created_time")]
     pub created_time: String,
+    /// The trade as the API or stream sent it
     #[serde(skip)]
-    pub market_title: Option<String>,
+    pub raw: serde_json::Value,
     // Note: Kalshi public API doesn't expose account IDs for privacy
-    // Use trade_id as proxy for tracking patterns
 }
 
-#[derive(Debug, Deserialize)]
-struct TradesResponse {
-    #[serde(default)]
-    trades: Vec<Trade>,
+impl Trade {
+    pub fn timestamp_secs(&self) -> Option<i64> {
+        chrono::DateTime::parse_from_rfc3339(&self.created_time)
+            .ok()
+            .map(|dt| dt.timestamp())
+    }
+
+    /// The taker's price in dollars: a NO taker pays the NO price, not the
+    /// YES price.
+    pub fn taker_price(&self) -> Option<Decimal> {
+        side_price(
+            &self.taker_side,
+            (self.yes_price_dollars, self.yes_price),
+            (self.no_price_dollars, self.no_price),
+        )
+    }
+
+    pub fn contracts(&self) -> Option<Decimal> {
+        self.count_fp.or(self.count)
+    }
+
+    /// Whether the trade carries a price and a count in either form.
+    pub fn is_complete(&self) -> bool {
+        self.taker_price().is_some() && self.contracts().is_some()
+    }
+
+    pub fn normalize(self) -> NormalizedTrade {
+        // Incomplete trades are dropped when parsed
+        let price = self.taker_price().unwrap_or_default();
+        let contracts = self.contracts().unwrap_or_default();
+        let notional = price * contracts;
+
+        NormalizedTrade {
+            platform: Platform::Kalshi,
+            timestamp: chrono::DateTime::parse_from_rfc3339(&self.created_time)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_default(),
+            // Extract outcome from ticker with the side they're taking
+            outcome: Some(parse_ticker_details(&self.ticker, &self.taker_side)),
+            trade_id: self.trade_id,
+            market_id: self.ticker,
+            market_title: None,
+            outcome_id: self.taker_side,
+            // Every public fill is a taker buying YES or NO
+            side: Side::Buy,
+            kind: TradeKind::Trade,
+            contracts,
+            price,
+            notional,
+            cost: Some(notional + taker_fee(price, contracts)),
+            // Each contract pays $1 if its side wins
+            max_payout: Some(contracts),
+            exposure: None,
+            actor_id: None,
+            market: None,
+            raw: self.raw,
+        }
+    }
 }
 
-pub async fn fetch_recent_trades(config: Option<&Config>) -> Result<Vec<Trade>, KalshiError> {
-    let client = reqwest::Client::new();
+/// Kalshi's general taker fee rate. Fees are this share of the contracts'
+/// expected earnings; a few markets, such as the S&P 500 and Nasdaq ranges,
+/// charge less.
+pub const TAKER_FEE_RATE: Decimal = dec!(0.07);
 
-    // Kalshi's public trades endpoint
-    let url = "https://api.elections.kalshi.com/trade-api/v2/markets/trades";
+/// Taker fee in dollars for `contracts` bought at `price` dollars each,
+/// rounded up to the cent as Kalshi does.
+pub fn taker_fee(price: Decimal, contracts: Decimal) -> Decimal {
+    let fee = TAKER_FEE_RATE * contracts * price * (Decimal::ONE - price);
+    fee.max(Decimal::ZERO)
+        .round_dp_with_strategy(2, RoundingStrategy::AwayFromZero)
+}
 
-    let mut request = client
-        .get(url)
-        .query(&[("limit", "100")])
-        .header("Accept", "application/json");
+struct TradesPage {
+    trades: ParsedPage<Trade>,
+    cursor: Option<String>,
+}
 
-    // Add authentication if credentials are provided
-    if let Some(cfg) = config {
-        if let (Some(key_id), Some(_private_key)) =
-            (&cfg.kalshi_api_key_id, &cfg.kalshi_private_key)
+const PAGE_SIZE: &str = "100";
+
+/// Follow the trades cursor backwards until `stop` matches a trade.
+///
+/// `ticker` limits the feed to one market, and `until` (unix seconds) starts
+/// it at that time instead of the newest trade. Returns the trades newer
+/// than the first match, newest first. If `stop` never matches within `max_pages`,
+/// the batch is flagged as a coverage gap.
+pub async fn fetch_trades_until<F>(
+    http: &HttpClient,
+    api_url: &str,
+    signer: Option<&KalshiSigner>,
+    ticker: Option<&str>,
+    until: Option<i64>,
+    max_pages: usize,
+    stop: F,
+) -> Result<TradeBatch<Trade>, KalshiError>
+where
+    F: Fn(&Trade) -> bool,
+{
+    let mut batch = TradeBatch::default();
+    let mut cursor: Option<String> = None;
+
+    for _ in 0..max_pages.max(1) {
+        let page = match fetch_trades_page(http, api_url, signer, ticker, until, cursor.as_deref())
+            .await
         {
-            // For simplicity, we'll use basic auth
-            // In production, you'd implement proper HMAC signature
-            request = request.header("KALSHI-ACCESS-KEY", key_id);
+            Ok(page) => page,
+            // Keep the newer pages we already have; the rest is a gap
+            Err(_) if batch.pages > 0 => {
+                batch.coverage_gap = true;
+                return Ok(batch);
+            }
+            Err(e) => return Err(e),
+        };
+        batch.add_page(&page.trades);
+
+        for trade in page.trades.items {
+            if stop(&trade) {
+                return Ok(batch);
+            }
+            batch.trades.push(trade);
+        }
+
+        cursor = page.cursor.filter(|c| !c.is_empty());
+        if cursor.is_none() {
+            return Ok(batch);
         }
     }
 
-    let response = request.send().await?;
+    batch.coverage_gap = true;
+    Ok(batch)
+}
 
-    if !response.status().is_success() {
-        return Err(KalshiError::ParseError(format!(
-            "API returned status: {}",
-            response.status()
-        )));
+async fn fetch_trades_page(
+    http: &HttpClient,
+    api_url: &str,
+    signer: Option<&KalshiSigner>,
+    ticker: Option<&str>,
+    until: Option<i64>,
+    cursor: Option<&str>,
+) -> Result<TradesPage, KalshiError> {
+    // Kalshi's public trades endpoint
+    let max_ts = until.map(|ts| ts.to_string());
+    let mut query = vec![("limit", PAGE_SIZE)];
+    if let Some(ticker) = ticker {
+        query.push(("ticker", ticker));
+    }
+    if let Some(max_ts) = max_ts.as_deref() {
+        query.push(("max_ts", max_ts));
+    }
+    if let Some(cursor) = cursor {
+        query.push(("cursor", cursor));
     }
+    let text = get(http, api_url, signer, "/markets/trades", &query).await?;
+    let schema_error = |message: &str| KalshiError::SchemaError {
+        message: message.to_string(),
+        sample: types::payload_sample(&text),
+    };
+
+    let body: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| schema_error(&e.to_string()))?;
+
+    let items = match body.get("trades") {
+        Some(serde_json::Value::Array(items)) => items.as_slice(),
+        Some(serde_json::Value::Null) => &[],
+        _ => return Err(schema_error("expected a `trades` array")),
+    };
+
+    Ok(TradesPage {
+        trades: types::parse_items(items, |item| {
+            let mut trade = Trade::deserialize(item).ok()?;
+            trade.timestamp_secs()?;
+            trade.is_complete().then_some(())?;
+            trade.raw = item.clone();
+            Some(trade)
+        }),
+        cursor: body
+            .get("cursor")
+            .and_then(|c| c.as_str())
+            .map(str::to_string),
+    })
+}
+
+/// Market details used to describe a trade.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Market {
+    pub ticker: String,
+    #[serde(default)]
+    pub event_ticker: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub subtitle: Option<String>,
+    #[serde(default)]
+    pub yes_sub_title: Option<String>,
+    #[serde(default)]
+    pub no_sub_title: Option<String>,
+    #[serde(default)]
+    pub close_time: Option<String>,
+    #[serde(default)]
+    pub category: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+}
 
-    let text = response.text().await?;
+impl Market {
+    pub fn display_title(&self) -> Option<&str> {
+        [&self.title, &self.subtitle]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .find(|t| !t.is_empty())
+    }
 
-    match serde_json::from_str::<TradesResponse>(&text) {
-        Ok(response) => Ok(response.trades),
-        Err(e) => {
-            // If parsing fails, return empty list to allow tool to continue
-            eprintln!("Warning: Failed to parse Kalshi response: {}", e);
-            Ok(Vec::new())
+    pub fn info(&self) -> MarketInfo {
+        MarketInfo {
+            end_date: self.close_time.clone(),
+            category: self.category.clone(),
+            event: self.event_ticker.clone(),
+            ..MarketInfo::default()
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
-struct MarketResponse {
-    market: MarketData,
+struct MarketsResponse {
+    #[serde(default)]
+    markets: Vec<Market>,
+}
+
+// Tickers per `/markets?tickers=` request
+const MARKETS_PER_REQUEST: usize = 100;
+
+/// Fetch details for several markets at once.
+///
+/// Tickers are sent in batches through the `tickers` filter of `/markets`;
+/// unknown tickers are simply missing from the result.
+pub async fn fetch_markets(
+    http: &HttpClient,
+    api_url: &str,
+    signer: Option<&KalshiSigner>,
+    tickers: &[String],
+) -> Result<Vec<Market>, KalshiError> {
+    let mut markets = Vec::new();
+
+    for chunk in tickers.chunks(MARKETS_PER_REQUEST) {
+        let tickers = chunk.join(",");
+        let limit = chunk.len().to_string();
+        let query = [("tickers", tickers.as_str()), ("limit", limit.as_str())];
+        let text = get(http, api_url, signer, "/markets", &query).await?;
+
+        let response = serde_json::from_str::<MarketsResponse>(&text)
+            .map_err(|e| KalshiError::ParseError(e.to_string()))?;
+        markets.extend(response.markets);
+    }
+
+    Ok(markets)
 }
 
 #[derive(Debug, Deserialize)]
-struct MarketData {
-    title: Option<String>,
-    subtitle: Option<String>,
-}
-
-pub async fn fetch_market_info(ticker: &str) -> Option<String> {
-    let client = reqwest::Client::new();
-    let url = format!(
-        "https://api.elections.kalshi.com/trade-api/v2/markets/{}",
-        ticker
-    );
-
-    match client.get(&url).send().await {
-        Ok(response) if response.status().is_success() => {
-            if let Ok(text) = response.text().await {
-                if let Ok(market_response) = serde_json::from_str::<MarketResponse>(&text) {
-                    return market_response
-                        .market
-                        .title
-                        .or(market_response.market.subtitle);
-                }
-            }
+struct MarketsPage {
+    #[serde(default)]
+    markets: Vec<Market>,
+    #[serde(default)]
+    cursor: Option<String>,
+}
+
+/// Fetch the open markets matching a `/markets` filter, such as
+/// `("event_ticker", "KXFEDDECISION-25DEC")` or `("series_ticker", "KXFEDDECISION")`.
+pub async fn fetch_open_markets(
+    http: &HttpClient,
+    api_url: &str,
+    signer: Option<&KalshiSigner>,
+    filter: (&str, &str),
+) -> Result<Vec<Market>, KalshiError> {
+    let mut markets = Vec::new();
+    let mut cursor: Option<String> = None;
+
+    loop {
+        let mut query = vec![filter, ("status", "open"), ("limit", "1000")];
+        if let Some(cursor) = cursor.as_deref() {
+            query.push(("cursor", cursor));
         }
-        _ => {}
+        let text = get(http, api_url, signer, "/markets", &query).await?;
+
+        let page = serde_json::from_str::<MarketsPage>(&text)
+            .map_err(|e| KalshiError::ParseError(e.to_string()))?;
+        markets.extend(page.markets);
+
+        cursor = page.cursor.filter(|c| !c.is_empty());
+        if cursor.is_none() {
+            return Ok(markets);
+        }
+    }
+}
+
+// Authenticated portfolio endpoints. These require a configured signer.
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Balance {
+    /// Available balance in cents
+    pub balance: i64,
+    /// Mark-to-market value of open positions in cents
+    #[serde(default)]
+    pub portfolio_value: Option<i64>,
+}
+
+/// The price in dollars of `side` ("yes" or "no"), from its dollar string
+/// or, failing that, its price in cents.
+fn side_price(
+    side: &str,
+    yes: (Option<Decimal>, Option<Decimal>),
+    no: (Option<Decimal>, Option<Decimal>),
+) -> Option<Decimal> {
+    let (dollars, cents) = if side.eq_ignore_ascii_case("no") {
+        no
+    } else {
+        yes
+    };
+    dollars.or(cents.map(|c| c / dec!(100)))
+}
+
+/// One of the account's fills. Prices and counts come in the same two forms
+/// as on a public `Trade`.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Fill {
+    pub ticker: String,
+    pub side: String,
+    pub action: String,
+    #[serde(default)]
+    pub count: Option<Decimal>,
+    #[serde(default)]
+    pub count_fp: Option<Decimal>,
+    /// In cents
+    #[serde(default)]
+    pub yes_price: Option<Decimal>,
+    /// In cents
+    #[serde(default)]
+    pub no_price: Option<Decimal>,
+    #[serde(default)]
+    pub yes_price_dollars: Option<Decimal>,
+    #[serde(default)]
+    pub no_price_dollars: Option<Decimal>,
+    #[serde(default)]
+    pub is_taker: bool,
+    pub created_time: String,
+}
+
+impl Fill {
+    /// The price paid for the side filled, in dollars.
+    pub fn price(&self) -> Option<Decimal> {
+        side_price(
+            &self.side,
+            (self.yes_price_dollars, self.yes_price),
+            (self.no_price_dollars, self.no_price),
+        )
+    }
+
+    pub fn contracts(&self) -> Option<Decimal> {
+        self.count_fp.or(self.count)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FillsResponse {
+    #[serde(default)]
+    fills: Vec<Fill>,
+}
+
+/// One of the account's orders, with prices and counts in either form.
+#[derive(Debug, Deserialize, Clone)]
+pub struct Order {
+    pub order_id: String,
+    pub ticker: String,
+    pub side: String,
+    pub action: String,
+    pub status: String,
+    /// In cents
+    #[serde(default)]
+    pub yes_price: Option<Decimal>,
+    /// In cents
+    #[serde(default)]
+    pub no_price: Option<Decimal>,
+    #[serde(default)]
+    pub yes_price_dollars: Option<Decimal>,
+    #[serde(default)]
+    pub no_price_dollars: Option<Decimal>,
+    #[serde(default)]
+    pub remaining_count: Option<Decimal>,
+    #[serde(default)]
+    pub remaining_count_fp: Option<Decimal>,
+}
+
+impl Order {
+    /// The limit price of the side ordered, in dollars.
+    pub fn price(&self) -> Option<Decimal> {
+        side_price(
+            &self.side,
+            (self.yes_price_dollars, self.yes_price),
+            (self.no_price_dollars, self.no_price),
+        )
     }
 
-    None
+    pub fn remaining(&self) -> Option<Decimal> {
+        self.remaining_count_fp.or(self.remaining_count)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct OrdersResponse {
+    #[serde(default)]
+    orders: Vec<Order>,
+}
+
+pub async fn fetch_balance(
+    http: &HttpClient,
+    api_url: &str,
+    signer: &KalshiSigner,
+) -> Result<Balance, KalshiError> {
+    let text = get(http, api_url, Some(signer), "/portfolio/balance", &[]).await?;
+
+    serde_json::from_str::<Balance>(&text).map_err(|e| KalshiError::ParseError(e.to_string()))
+}
+
+pub async fn fetch_fills(
+    http: &HttpClient,
+    api_url: &str,
+    signer: &KalshiSigner,
+    limit: usize,
+) -> Result<Vec<Fill>, KalshiError> {
+    let limit = limit.to_string();
+    let text = get(
+        http,
+        api_url,
+        Some(signer),
+        "/portfolio/fills",
+        &[("limit", &limit)],
+    )
+    .await?;
+
+    serde_json::from_str::<FillsResponse>(&text)
+        .map(|r| r.fills)
+        .map_err(|e| KalshiError::ParseError(e.to_string()))
+}
+
+pub async fn fetch_orders(
+    http: &HttpClient,
+    api_url: &str,
+    signer: &KalshiSigner,
+    status: &str,
+    limit: usize,
+) -> Result<Vec<Order>, KalshiError> {
+    let limit = limit.to_string();
+    let text = get(
+        http,
+        api_url,
+        Some(signer),
+        "/portfolio/orders",
+        &[("status", status), ("limit", &limit)],
+    )
+    .await?;
+
+    serde_json::from_str::<OrdersResponse>(&text)
+        .map(|r| r.orders)
+        .map_err(|e| KalshiError::ParseError(e.to_string()))
 }
 
 pub fn parse_ticker_details(ticker: &str, side: &str) -> String {
@@ -411,3 +971,540 @@ pub fn parse_ticker_details(ticker: &str, side: &str) -> String {
         String::from("NO - check market details")
     }
 }
+
+/// Kalshi adapter: public trades, optionally the WebSocket trade channel,
+/// and `/markets` details.
+pub struct KalshiSource {
+    http: HttpClient,
+    api_url: String,
+    ws_url: Option<String>,
+    signer: Option<Arc<KalshiSigner>>,
+    markets: MarketCache<Market>,
+    /// Markets to watch (default: all)
+    scope: KalshiScope,
+    /// Open markets of the scope's events and series, and when they were
+    /// last looked up
+    scope_tickers: Vec<String>,
+    scope_resolved: Option<Instant>,
+}
+
+// New markets open in a series all the time, so its tickers are looked up again
+const SCOPE_REFRESH: Duration = Duration::from_secs(600);
+
+impl KalshiSource {
+    pub fn new(
+        http: HttpClient,
+        api_url: String,
+        signer: Option<Arc<KalshiSigner>>,
+        markets: MarketCache<Market>,
+    ) -> Self {
+        Self {
+            http,
+            api_url,
+            ws_url: None,
+            signer,
+            markets,
+            scope: KalshiScope::default(),
+            scope_tickers: Vec::new(),
+            scope_resolved: None,
+        }
+    }
+
+    /// Only watch trades in these markets, events or series.
+    pub fn with_scope(mut self, scope: KalshiScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn with_stream(mut self, ws_url: String) -> Self {
+        self.ws_url = Some(ws_url);
+        self
+    }
+
+    // The tickers to query: those named directly plus the open markets of
+    // the selected events and series
+    async fn scope_tickers(&mut self) -> Result<Vec<String>, KalshiError> {
+        if self.scope.events.is_empty() && self.scope.series.is_empty() {
+            return Ok(self.scope.tickers.clone());
+        }
+
+        if self
+            .scope_resolved
+            .is_none_or(|at| at.elapsed() >= SCOPE_REFRESH)
+        {
+            let filters = self
+                .scope
+                .events
+                .iter()
+                .map(|e| ("event_ticker", e.as_str()))
+                .chain(
+                    self.scope
+                        .series
+                        .iter()
+                        .map(|s| ("series_ticker", s.as_str())),
+                );
+
+            let mut tickers = self.scope.tickers.clone();
+            for filter in filters {
+                let found =
+                    fetch_open_markets(&self.http, &self.api_url, self.signer.as_deref(), filter)
+                        .await?;
+                for market in found {
+                    if !tickers.contains(&market.ticker) {
+                        tickers.push(market.ticker.clone());
+                    }
+                    // Details come with the lookup, so alerts needn't fetch them
+                    self.markets.insert(market.ticker.clone(), market);
+                }
+            }
+
+            self.scope_tickers = tickers;
+            self.scope_resolved = Some(Instant::now());
+        }
+
+        Ok(self.scope_tickers.clone())
+    }
+}
+
+impl TradeSource for KalshiSource {
+    fn platform(&self) -> Platform {
+        Platform::Kalshi
+    }
+
+    async fn fetch_trades(
+        &mut self,
+        max_pages: usize,
+        stop_before: Option<i64>,
+    ) -> Result<TradeBatch<NormalizedTrade>, SourceError> {
+        let stop = |t: &Trade| source::is_older(t.timestamp_secs(), stop_before);
+
+        if self.scope.is_empty() {
+            let batch = fetch_trades_until(
+                &self.http,
+                &self.api_url,
+                self.signer.as_deref(),
+                None,
+                None,
+                max_pages,
+                stop,
+            )
+            .await?;
+            return Ok(batch.map(Trade::normalize));
+        }
+
+        // The trades endpoint takes a single ticker, so each market is
+        // fetched on its own
+        let mut batch = TradeBatch::default();
+        for ticker in self.scope_tickers().await? {
+            let signer = self.signer.as_deref();
+            batch.merge(
+                fetch_trades_until(
+                    &self.http,
+                    &self.api_url,
+                    signer,
+                    Some(&ticker),
+                    None,
+                    max_pages,
+                    stop,
+                )
+                .await?,
+            );
+        }
+        batch
+            .trades
+            .sort_by_key(|t| std::cmp::Reverse(t.timestamp_secs()));
+
+        Ok(batch.map(Trade::normalize))
+    }
+
+    async fn fetch_range(
+        &mut self,
+        since: i64,
+        until: i64,
+        max_pages: usize,
+    ) -> Result<TradeBatch<NormalizedTrade>, SourceError> {
+        // The trades endpoint can start at `until`, so only the range is paged
+        let batch = fetch_trades_until(
+            &self.http,
+            &self.api_url,
+            self.signer.as_deref(),
+            None,
+            Some(until),
+            max_pages,
+            |t| source::is_older(t.timestamp_secs(), Some(since)),
+        )
+        .await?;
+        Ok(batch.map(Trade::normalize))
+    }
+
+    fn take_stream(&mut self) -> Option<mpsc::Receiver<StreamEvent<NormalizedTrade>>> {
+        let ws_url = self.ws_url.clone()?;
+        Some(crate::kalshi_ws::spawn_trade_stream(
+            ws_url,
+            self.signer.clone(),
+            self.scope.clone(),
+        ))
+    }
+
+    async fn enrich(&mut self, trades: &mut [NormalizedTrade]) {
+        let missing = self
+            .markets
+            .missing(trades.iter().map(|t| t.market_id.as_str()));
+
+        if !missing.is_empty() {
+            match fetch_markets(&self.http, &self.api_url, self.signer.as_deref(), &missing).await {
+                Ok(found) => {
+                    for market in found {
+                        self.markets.insert(market.ticker.clone(), market);
+                    }
+                }
+                Err(e) => {
+                    eprintln!(
+                        "{} Kalshi market lookup failed: {}",
+                        "[WARNING]".yellow(),
+                        e
+                    );
+                }
+            }
+        }
+
+        for trade in trades {
+            if let Some(market) = self.markets.get(&trade.market_id) {
+                trade.market_title = market.display_title().map(str::to_string);
+                trade.market = Some(market.info());
+            }
+        }
+    }
+
+    fn save(&mut self) -> std::io::Result<()> {
+        self.markets.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rsa::pss::{Signature, VerifyingKey};
+    use rsa::signature::Verifier;
+    use serde_json::json;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    // A key made for these tests only
+    const TEST_KEY: &str = include_str!("../tests/fixtures/kalshi/test_key.pem");
+    // `openssl dgst -sha256 -sigopt rsa_padding_mode:pss -sigopt
+    // rsa_pss_saltlen:digest` over `1700000000000GET/trade-api/v2/portfolio/balance`
+    const BALANCE_SIGNATURE: &str = include_str!("../tests/fixtures/kalshi/balance_signature.b64");
+
+    fn verifying_key() -> VerifyingKey<Sha256> {
+        let private_key = RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        VerifyingKey::new(private_key.to_public_key())
+    }
+
+    fn verifies(message: &str, signature: &str) -> bool {
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(signature.trim())
+            .unwrap();
+        let signature = Signature::try_from(bytes.as_slice()).unwrap();
+        verifying_key()
+            .verify(message.as_bytes(), &signature)
+            .is_ok()
+    }
+
+    #[test]
+    fn signs_timestamp_method_and_path() {
+        let signer = KalshiSigner::new("key-id", TEST_KEY).unwrap();
+        let signature = signer.sign(1700000000000, "get", "/trade-api/v2/portfolio/balance");
+
+        assert!(verifies(
+            "1700000000000GET/trade-api/v2/portfolio/balance",
+            &signature
+        ));
+        assert!(!verifies(
+            "1700000000001GET/trade-api/v2/portfolio/balance",
+            &signature
+        ));
+        assert!(!verifies(
+            "1700000000000POST/trade-api/v2/portfolio/balance",
+            &signature
+        ));
+    }
+
+    #[test]
+    fn known_signature_verifies() {
+        assert!(verifies(
+            "1700000000000GET/trade-api/v2/portfolio/balance",
+            BALANCE_SIGNATURE
+        ));
+    }
+
+    #[test]
+    fn accepts_pkcs1_keys() {
+        let private_key = RsaPrivateKey::from_pkcs8_pem(TEST_KEY).unwrap();
+        let pkcs1 =
+            rsa::pkcs1::EncodeRsaPrivateKey::to_pkcs1_pem(&private_key, rsa::pkcs1::LineEnding::LF)
+                .unwrap();
+        let signer = KalshiSigner::new("key-id", &pkcs1).unwrap();
+        let signature = signer.sign(1700000000000, "GET", "/trade-api/v2/portfolio/balance");
+
+        assert!(verifies(
+            "1700000000000GET/trade-api/v2/portfolio/balance",
+            &signature
+        ));
+    }
+
+    #[tokio::test]
+    async fn query_string_is_not_signed() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}")
+                .await
+                .unwrap();
+            String::from_utf8(request).unwrap()
+        });
+
+        let http = HttpClient::new(crate::http::HttpSettings::from_config(&Config::default()));
+        let signer = KalshiSigner::new("key-id", TEST_KEY).unwrap();
+        let api_url = format!("http://{}/trade-api/v2", addr);
+        get(
+            &http,
+            &api_url,
+            Some(&signer),
+            "/portfolio/fills",
+            &[("limit", "5")],
+        )
+        .await
+        .unwrap();
+
+        let request = server.await.unwrap();
+        let header = |name: &str| {
+            request
+                .lines()
+                .find_map(|line| {
+                    let (key, value) = line.split_once(": ")?;
+                    key.eq_ignore_ascii_case(name).then(|| value.to_string())
+                })
+                .unwrap()
+        };
+        assert!(request.starts_with("GET /trade-api/v2/portfolio/fills?limit=5 HTTP/1.1"));
+        assert_eq!(header("KALSHI-ACCESS-KEY"), "key-id");
+
+        let timestamp = header("KALSHI-ACCESS-TIMESTAMP");
+        let signature = header("KALSHI-ACCESS-SIGNATURE");
+        assert!(verifies(
+            &format!("{}GET/trade-api/v2/portfolio/fills", timestamp),
+            &signature
+        ));
+        assert!(!verifies(
+            &format!("{}GET/trade-api/v2/portfolio/fills?limit=5", timestamp),
+            &signature
+        ));
+    }
+
+    fn trade(fields: serde_json::Value) -> NormalizedTrade {
+        let mut item = json!({
+            "trade_id": "t1",
+            "ticker": "KXBTCD-25DEC31-B100000",
+            "created_time": "2026-10-17T12:00:00Z",
+        });
+        item.as_object_mut()
+            .unwrap()
+            .extend(fields.as_object().unwrap().clone());
+        let trade = Trade::deserialize(&item).unwrap();
+        assert!(trade.is_complete());
+        trade.normalize()
+    }
+
+    // 37 YES at 36c: 0.07 * 37 * 0.36 * 0.64 = 0.596736, rounded up
+    fn assert_yes_taker(trade: NormalizedTrade) {
+        assert_eq!(trade.outcome_id, "yes");
+        assert_eq!(trade.side, Side::Buy);
+        assert_eq!(trade.price, dec!(0.36));
+        assert_eq!(trade.contracts, dec!(37));
+        assert_eq!(trade.notional, dec!(13.32));
+        assert_eq!(trade.cost, Some(dec!(13.92)));
+        assert_eq!(trade.max_payout, Some(dec!(37)));
+    }
+
+    // 10 NO at 64c: 0.07 * 10 * 0.64 * 0.36 = 0.16128, rounded up
+    fn assert_no_taker(trade: NormalizedTrade) {
+        assert_eq!(trade.outcome_id, "no");
+        assert_eq!(trade.side, Side::Buy);
+        assert_eq!(trade.price, dec!(0.64));
+        assert_eq!(trade.contracts, dec!(10));
+        assert_eq!(trade.notional, dec!(6.40));
+        assert_eq!(trade.cost, Some(dec!(6.57)));
+        assert_eq!(trade.max_payout, Some(dec!(10)));
+    }
+
+    #[test]
+    fn yes_taker_from_cents() {
+        assert_yes_taker(trade(json!({
+            "taker_side": "yes",
+            "count": 37,
+            "yes_price": 36,
+            "no_price": 64,
+        })));
+    }
+
+    #[test]
+    fn yes_taker_from_dollars() {
+        assert_yes_taker(trade(json!({
+            "taker_side": "yes",
+            "count_fp": "37.00",
+            "yes_price_dollars": "0.3600",
+            "no_price_dollars": "0.6400",
+        })));
+    }
+
+    #[test]
+    fn no_taker_from_cents() {
+        assert_no_taker(trade(json!({
+            "taker_side": "no",
+            "count": 10,
+            "yes_price": 36,
+            "no_price": 64,
+        })));
+    }
+
+    #[test]
+    fn no_taker_from_dollars() {
+        assert_no_taker(trade(json!({
+            "taker_side": "no",
+            "count_fp": "10.00",
+            "yes_price_dollars": "0.3600",
+            "no_price_dollars": "0.6400",
+        })));
+    }
+
+    #[test]
+    fn fixed_point_fields_win() {
+        let trade = trade(json!({
+            "taker_side": "yes",
+            "count": 1,
+            "count_fp": "2.50",
+            "yes_price": 10,
+            "yes_price_dollars": "0.5000",
+        }));
+        assert_eq!(trade.contracts, dec!(2.50));
+        assert_eq!(trade.price, dec!(0.5));
+    }
+
+    #[test]
+    fn fee_rounds_up_to_the_cent() {
+        // 0.07 * 2.5 * 0.5 * 0.5 = 0.04375
+        assert_eq!(taker_fee(dec!(0.5), dec!(2.5)), dec!(0.05));
+        // 0.07 * 100 * 0.5 * 0.5 = 1.75 exactly
+        assert_eq!(taker_fee(dec!(0.5), dec!(100)), dec!(1.75));
+        assert_eq!(taker_fee(dec!(0.01), dec!(1)), dec!(0.01));
+        assert_eq!(taker_fee(dec!(1), dec!(100)), dec!(0));
+    }
+
+    #[tokio::test]
+    async fn markets_are_fetched_a_hundred_at_a_time() {
+        const MARKETS: &str = include_str!("../tests/fixtures/kalshi/markets.json");
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let (targets_tx, mut targets) = tokio::sync::mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8(request).unwrap();
+                let target = request.split_whitespace().nth(1).unwrap().to_string();
+                targets_tx.send(target).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    MARKETS.len(),
+                    MARKETS
+                );
+                socket.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        let http = HttpClient::new(crate::http::HttpSettings::from_config(&Config::default()));
+        let api_url = format!("http://{}/trade-api/v2", addr);
+        let tickers: Vec<String> = (0..250).map(|i| format!("KXTEST-{}", i)).collect();
+        let markets = fetch_markets(&http, &api_url, None, &tickers)
+            .await
+            .unwrap();
+        assert_eq!(markets.len(), 3);
+        assert_eq!(
+            markets[0].display_title(),
+            Some("Bitcoin price on Dec 31, 2025?")
+        );
+
+        let mut requested = Vec::new();
+        while let Ok(target) = targets.try_recv() {
+            let url = reqwest::Url::parse(&format!("http://{}{}", addr, target)).unwrap();
+            assert_eq!(url.path(), "/trade-api/v2/markets");
+            let query: std::collections::HashMap<_, _> = url.query_pairs().into_owned().collect();
+            let chunk: Vec<String> = query["tickers"].split(',').map(str::to_string).collect();
+            assert_eq!(query["limit"], chunk.len().to_string());
+            requested.push(chunk);
+        }
+        assert_eq!(
+            requested.iter().map(Vec::len).collect::<Vec<_>>(),
+            [100, 100, 50]
+        );
+        assert_eq!(requested.concat(), tickers);
+    }
+
+    // Each fixture holds a 37 YES fill or order at 36c and a 10 NO one at 64c
+    fn assert_fills(json: &str) {
+        let fills = serde_json::from_str::<FillsResponse>(json).unwrap().fills;
+        let parsed: Vec<_> = fills.iter().map(|f| (f.price(), f.contracts())).collect();
+        assert_eq!(
+            parsed,
+            [
+                (Some(dec!(0.36)), Some(dec!(37))),
+                (Some(dec!(0.64)), Some(dec!(10)))
+            ]
+        );
+    }
+
+    fn assert_orders(json: &str) {
+        let orders = serde_json::from_str::<OrdersResponse>(json).unwrap().orders;
+        let parsed: Vec<_> = orders.iter().map(|o| (o.price(), o.remaining())).collect();
+        assert_eq!(
+            parsed,
+            [
+                (Some(dec!(0.36)), Some(dec!(37))),
+                (Some(dec!(0.64)), Some(dec!(10)))
+            ]
+        );
+    }
+
+    #[test]
+    fn fills_from_cents() {
+        assert_fills(include_str!("../tests/fixtures/kalshi/fills_cents.json"));
+    }
+
+    #[test]
+    fn fills_from_dollars() {
+        assert_fills(include_str!("../tests/fixtures/kalshi/fills_dollars.json"));
+    }
+
+    #[test]
+    fn orders_from_cents() {
+        assert_orders(include_str!("../tests/fixtures/kalshi/orders_cents.json"));
+    }
+
+    #[test]
+    fn orders_from_dollars() {
+        assert_orders(include_str!("../tests/fixtures/kalshi/orders_dollars.json"));
+    }
+}
@@ -0,0 +1,332 @@
+// Kalshi WebSocket trade stream
+//
+// Subscribes to the public `trade` channel and forwards every fill as a
+// normalized trade, reconnecting with exponential backoff when the socket drops.
+
+use crate::kalshi::{KalshiSigner, Trade};
+use crate::types::{NormalizedTrade, StreamEvent, STREAM_INITIAL_BACKOFF, STREAM_MAX_BACKOFF};
+use crate::watchlist::KalshiScope;
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::HeaderValue;
+use tokio_tungstenite::tungstenite::Message;
+
+#[derive(Debug, Deserialize)]
+struct WsEnvelope {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    msg: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct WsTrade {
+    trade_id: String,
+    market_ticker: String,
+    #[serde(default)]
+    yes_price: Option<Decimal>,
+    #[serde(default)]
+    no_price: Option<Decimal>,
+    #[serde(default)]
+    yes_price_dollars: Option<Decimal>,
+    #[serde(default)]
+    no_price_dollars: Option<Decimal>,
+    #[serde(default)]
+    count: Option<Decimal>,
+    #[serde(default)]
+    count_fp: Option<Decimal>,
+    taker_side: String,
+    ts: i64,
+}
+
+impl WsTrade {
+    fn into_trade(self, raw: serde_json::Value) -> Trade {
+        let t = self;
+        Trade {
+            trade_id: t.trade_id,
+            ticker: t.market_ticker,
+            count: t.count,
+            count_fp: t.count_fp,
+            yes_price: t.yes_price,
+            no_price: t.no_price,
+            yes_price_dollars: t.yes_price_dollars,
+            no_price_dollars: t.no_price_dollars,
+            taker_side: t.taker_side,
+            created_time: chrono::DateTime::from_timestamp(t.ts, 0)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            raw,
+        }
+    }
+}
+
+/// Spawn a background task that keeps a trade subscription open.
+///
+/// Only trades in `scope` are forwarded. The task runs until the receiver is
+/// dropped.
+pub fn spawn_trade_stream(
+    url: String,
+    signer: Option<Arc<KalshiSigner>>,
+    scope: KalshiScope,
+) -> mpsc::Receiver<StreamEvent<NormalizedTrade>> {
+    let (tx, rx) = mpsc::channel(1024);
+
+    tokio::spawn(async move {
+        let mut backoff = STREAM_INITIAL_BACKOFF;
+
+        loop {
+            let reason = match run_session(&url, signer.as_deref(), &scope, &tx, &mut backoff).await
+            {
+                Ok(()) => "connection closed by server".to_string(),
+                Err(e) => e,
+            };
+
+            if tx.send(StreamEvent::Disconnected(reason)).await.is_err() {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(STREAM_MAX_BACKOFF);
+        }
+    });
+
+    rx
+}
+
+async fn run_session(
+    url: &str,
+    signer: Option<&KalshiSigner>,
+    scope: &KalshiScope,
+    tx: &mpsc::Sender<StreamEvent<NormalizedTrade>>,
+    backoff: &mut Duration,
+) -> Result<(), String> {
+    let mut request = url.into_client_request().map_err(|e| e.to_string())?;
+
+    if let Some(signer) = signer {
+        let path = request.uri().path().to_string();
+        for (name, value) in signer.headers("GET", &path) {
+            let value = HeaderValue::from_str(&value).map_err(|e| e.to_string())?;
+            request.headers_mut().insert(name, value);
+        }
+    }
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // Named markets can be subscribed to directly; events and series are
+    // filtered here since their markets change over time
+    let mut params = json!({ "channels": ["trade"] });
+    if scope.events.is_empty() && scope.series.is_empty() && !scope.tickers.is_empty() {
+        params["market_tickers"] = json!(scope.tickers);
+    }
+    let subscribe = json!({
+        "id": 1,
+        "cmd": "subscribe",
+        "params": params,
+    });
+    socket
+        .send(Message::Text(subscribe.to_string()))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    *backoff = STREAM_INITIAL_BACKOFF;
+    if tx.send(StreamEvent::Connected).await.is_err() {
+        return Ok(());
+    }
+
+    while let Some(message) = socket.next().await {
+        let text = match message.map_err(|e| e.to_string())? {
+            Message::Text(text) => text,
+            Message::Ping(payload) => {
+                socket
+                    .send(Message::Pong(payload))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let Ok(envelope) = serde_json::from_str::<WsEnvelope>(&text) else {
+            continue;
+        };
+
+        match envelope.kind.as_str() {
+            "trade" => {
+                let Some(msg) = envelope.msg else {
+                    continue;
+                };
+                if let Ok(trade) = WsTrade::deserialize(&msg) {
+                    if !scope.matches(&trade.market_ticker) {
+                        continue;
+                    }
+                    let trade = trade.into_trade(msg);
+                    if !trade.is_complete() {
+                        continue;
+                    }
+                    if tx
+                        .send(StreamEvent::Trade(trade.normalize()))
+                        .await
+                        .is_err()
+                    {
+                        return Ok(());
+                    }
+                }
+            }
+            "error" => {
+                return Err(format!(
+                    "subscription error: {}",
+                    envelope.msg.map(|m| m.to_string()).unwrap_or_default()
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+    use tokio::net::TcpListener;
+    use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+
+    const TEST_KEY: &str = include_str!("../tests/fixtures/kalshi/test_key.pem");
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn trade_message(trade: serde_json::Value) -> Message {
+        Message::Text(json!({ "type": "trade", "sid": 1, "msg": trade }).to_string())
+    }
+
+    async fn next_event(
+        rx: &mut mpsc::Receiver<StreamEvent<NormalizedTrade>>,
+    ) -> StreamEvent<NormalizedTrade> {
+        tokio::time::timeout(TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for a stream event")
+            .expect("stream task ended")
+    }
+
+    async fn next_trade(rx: &mut mpsc::Receiver<StreamEvent<NormalizedTrade>>) -> NormalizedTrade {
+        match next_event(rx).await {
+            StreamEvent::Trade(trade) => trade,
+            _ => panic!("expected a trade"),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribes_decodes_and_reconnects() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/trade-api/ws/v2", listener.local_addr().unwrap());
+        // What the server saw on each connection: the key header and the subscribe command
+        let (seen_tx, mut seen_rx) = mpsc::unbounded_channel::<(Option<String>, String)>();
+
+        tokio::spawn(async move {
+            for session in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut key = None;
+                // The handshake callback's error type is tungstenite's, not ours
+                #[allow(clippy::result_large_err)]
+                let mut socket = tokio_tungstenite::accept_hdr_async(
+                    stream,
+                    |request: &Request, response: Response| {
+                        key = request
+                            .headers()
+                            .get("KALSHI-ACCESS-KEY")
+                            .and_then(|v| v.to_str().ok())
+                            .map(str::to_string);
+                        Ok(response)
+                    },
+                )
+                .await
+                .unwrap();
+                let Some(Ok(Message::Text(subscribe))) = socket.next().await else {
+                    panic!("expected a subscribe command");
+                };
+                seen_tx.send((key, subscribe)).unwrap();
+
+                if session == 0 {
+                    // Out of scope, then missing its price, then the one to forward
+                    let trades = [
+                        json!({"trade_id": "t0", "market_ticker": "KXOTHER-25-T1",
+                               "yes_price": 50, "no_price": 50, "count": 10,
+                               "taker_side": "yes", "ts": 1700000000}),
+                        json!({"trade_id": "t1", "market_ticker": "KXBTC-25-T1",
+                               "count": 10, "taker_side": "yes", "ts": 1700000000}),
+                        json!({"trade_id": "t2", "market_ticker": "KXBTC-25-T1",
+                               "yes_price": 36, "no_price": 64, "count": 37,
+                               "taker_side": "yes", "ts": 1700000000}),
+                    ];
+                    for trade in trades {
+                        socket.send(trade_message(trade)).await.unwrap();
+                    }
+                    // Drop the socket without a close frame
+                    drop(socket);
+                } else {
+                    let trade = json!({"trade_id": "t3", "market_ticker": "KXBTC-25-T1",
+                                       "yes_price_dollars": "0.3600", "no_price_dollars": "0.6400",
+                                       "count_fp": "10.00", "taker_side": "no", "ts": 1700000060});
+                    socket.send(trade_message(trade)).await.unwrap();
+                    // Hold the connection open until the test ends
+                    while socket.next().await.is_some() {}
+                }
+            }
+        });
+
+        let signer = KalshiSigner::new("key-id", TEST_KEY).unwrap();
+        let scope = KalshiScope {
+            tickers: vec!["KXBTC-25-T1".to_string()],
+            ..Default::default()
+        };
+        let mut rx = spawn_trade_stream(url, Some(Arc::new(signer)), scope);
+
+        assert!(matches!(next_event(&mut rx).await, StreamEvent::Connected));
+        let (key, subscribe) = seen_rx.recv().await.unwrap();
+        assert_eq!(key.as_deref(), Some("key-id"));
+        let subscribe: serde_json::Value = serde_json::from_str(&subscribe).unwrap();
+        assert_eq!(subscribe["cmd"], "subscribe");
+        assert_eq!(subscribe["params"]["channels"], json!(["trade"]));
+        assert_eq!(
+            subscribe["params"]["market_tickers"],
+            json!(["KXBTC-25-T1"])
+        );
+
+        let trade = next_trade(&mut rx).await;
+        assert_eq!(trade.trade_id, "t2");
+        assert_eq!(trade.market_id, "KXBTC-25-T1");
+        assert_eq!(trade.outcome_id, "yes");
+        assert_eq!(trade.price, dec!(0.36));
+        assert_eq!(trade.contracts, dec!(37));
+        assert_eq!(trade.notional, dec!(13.32));
+        assert_eq!(trade.timestamp.timestamp(), 1_700_000_000);
+        assert_eq!(trade.raw["trade_id"], "t2");
+
+        // The drop is reported, so the watcher can poll for what it misses
+        assert!(matches!(
+            next_event(&mut rx).await,
+            StreamEvent::Disconnected(_)
+        ));
+
+        // After the backoff it reconnects and subscribes again
+        assert!(matches!(next_event(&mut rx).await, StreamEvent::Connected));
+        let (key, subscribe) = seen_rx.recv().await.unwrap();
+        assert_eq!(key.as_deref(), Some("key-id"));
+        assert!(subscribe.contains("\"subscribe\""));
+
+        let trade = next_trade(&mut rx).await;
+        assert_eq!(trade.trade_id, "t3");
+        assert_eq!(trade.outcome_id, "no");
+        assert_eq!(trade.price, dec!(0.64));
+        assert_eq!(trade.contracts, dec!(10));
+    }
+}
@@ -1,11 +1,29 @@
+mod actors;
 mod config;
+mod http;
 mod kalshi;
+mod kalshi_ws;
+mod manifold;
+mod market_cache;
 mod polymarket;
+mod polymarket_ws;
+mod positions;
+mod profile;
+mod recording;
+mod seen;
+mod source;
 mod types;
+mod wallet_store;
+mod watchlist;
 
 use clap::{Parser, Subcommand};
 use colored::*;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use std::collections::HashMap;
 use std::io::{self, Write};
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time;
 
@@ -15,19 +33,221 @@ use tokio::time;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    #[command(flatten)]
+    endpoints: EndpointArgs,
+}
+
+/// API endpoint overrides (take precedence over the config file)
+#[derive(clap::Args)]
+struct EndpointArgs {
+    /// Kalshi environment: prod or demo
+    #[arg(long, global = true, env = "WWATCHER_KALSHI_ENV", value_enum)]
+    kalshi_env: Option<config::KalshiEnv>,
+
+    /// Kalshi REST API base URL, including the /trade-api/v2 prefix
+    #[arg(
+        long,
+        global = true,
+        env = "WWATCHER_KALSHI_API_URL",
+        value_name = "URL"
+    )]
+    kalshi_api_url: Option<String>,
+
+    /// Kalshi WebSocket URL
+    #[arg(
+        long,
+        global = true,
+        env = "WWATCHER_KALSHI_WS_URL",
+        value_name = "URL"
+    )]
+    kalshi_ws_url: Option<String>,
+
+    /// Polymarket Data API base URL
+    #[arg(
+        long,
+        global = true,
+        env = "WWATCHER_POLYMARKET_API_URL",
+        value_name = "URL"
+    )]
+    polymarket_api_url: Option<String>,
+
+    /// Polymarket Gamma API base URL
+    #[arg(
+        long,
+        global = true,
+        env = "WWATCHER_POLYMARKET_GAMMA_URL",
+        value_name = "URL"
+    )]
+    polymarket_gamma_url: Option<String>,
+
+    /// Polymarket CLOB WebSocket URL
+    #[arg(
+        long,
+        global = true,
+        env = "WWATCHER_POLYMARKET_WS_URL",
+        value_name = "URL"
+    )]
+    polymarket_ws_url: Option<String>,
+
+    /// Manifold API base URL, including the /v0 prefix
+    #[arg(
+        long,
+        global = true,
+        env = "WWATCHER_MANIFOLD_API_URL",
+        value_name = "URL"
+    )]
+    manifold_api_url: Option<String>,
+}
+
+impl From<EndpointArgs> for config::EndpointOverrides {
+    fn from(args: EndpointArgs) -> Self {
+        Self {
+            kalshi_env: args.kalshi_env,
+            kalshi_api_url: args.kalshi_api_url,
+            kalshi_ws_url: args.kalshi_ws_url,
+            polymarket_data_api_url: args.polymarket_api_url,
+            polymarket_gamma_api_url: args.polymarket_gamma_url,
+            polymarket_ws_url: args.polymarket_ws_url,
+            manifold_api_url: args.manifold_api_url,
+        }
+    }
+}
+
+#[derive(clap::Args)]
+struct WatchArgs {
+    /// Minimum transaction size to alert on (in USD)
+    #[arg(short, long, default_value = "25000")]
+    threshold: u64,
+
+    /// Polling interval in seconds
+    #[arg(short, long, default_value = "5")]
+    interval: u64,
+
+    /// Maximum pages (of 100 trades) to walk back per poll looking for the last seen trade
+    #[arg(long, default_value = "10")]
+    max_pages: usize,
+
+    /// Remember handled trades on disk so a restart doesn't re-alert on them
+    #[arg(long)]
+    persist_seen: bool,
+
+    /// Keep fetched market details on disk so a restart doesn't look them up again
+    #[arg(long)]
+    persist_markets: bool,
+
+    /// Keep wallet activity on disk so repeat and heavy actors are still recognised after a restart
+    #[arg(long)]
+    persist_wallets: bool,
+
+    /// Stream Kalshi trades over WebSocket instead of polling (polling resumes while disconnected)
+    #[arg(long)]
+    kalshi_stream: bool,
+
+    /// Stream Polymarket trades from the CLOB WebSocket market channel instead of polling
+    #[arg(long)]
+    polymarket_stream: bool,
+
+    /// Condition IDs to stream (comma separated; default: most active markets)
+    #[arg(long, value_name = "CONDITION_IDS", value_delimiter = ',')]
+    polymarket_markets: Vec<String>,
+
+    /// Platforms to watch (comma separated; default: those enabled in the config)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    platforms: Vec<types::Platform>,
+
+    /// Polymarket polling interval in seconds (default: --interval)
+    #[arg(long, value_name = "SECONDS")]
+    polymarket_interval: Option<u64>,
+
+    /// Kalshi polling interval in seconds (default: --interval)
+    #[arg(long, value_name = "SECONDS")]
+    kalshi_interval: Option<u64>,
+
+    /// Manifold polling interval in seconds (default: --interval)
+    #[arg(long, value_name = "SECONDS")]
+    manifold_interval: Option<u64>,
+
+    /// Only watch these Polymarket markets (condition IDs or slugs, comma separated)
+    #[arg(long, value_name = "ID_OR_SLUG", value_delimiter = ',')]
+    market: Vec<String>,
+
+    /// Only watch these Kalshi markets (comma separated)
+    #[arg(long, value_name = "TICKER", value_delimiter = ',')]
+    ticker: Vec<String>,
+
+    /// Only watch the markets of these Kalshi series (comma separated)
+    #[arg(long, value_name = "SERIES_TICKER", value_delimiter = ',')]
+    series: Vec<String>,
+
+    /// Only watch the markets of these Kalshi events (comma separated)
+    #[arg(long, value_name = "EVENT_TICKER", value_delimiter = ',')]
+    event: Vec<String>,
+
+    /// Only watch the markets in the config file's watchlist
+    #[arg(long)]
+    watchlist: bool,
+
+    /// Save every raw API response to this directory, for `wwatcher replay`
+    #[arg(long, value_name = "DIR")]
+    record: Option<std::path::PathBuf>,
+
+    /// Repeat/heavy actor window, e.g. 24h,txns=5,level=heavy or 5m,txns=3,volume=50000 (repeatable; replaces the config file's)
+    #[arg(long, value_name = "SPEC")]
+    actor_window: Vec<actors::ActorWindow>,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Watch for large transactions (default threshold: $25,000)
-    Watch {
+    Watch(WatchArgs),
+    /// Watch, also polling these Polymarket wallets' activity and alerting on every trade, split, merge and redeem they make
+    Follow {
+        /// Wallet addresses
+        #[arg(required = true)]
+        addresses: Vec<String>,
+
+        #[command(flatten)]
+        watch: WatchArgs,
+    },
+    /// Play back a recording made with `watch --record` or `follow --record`, without network access
+    Replay {
+        /// Directory the recording was saved to
+        dir: std::path::PathBuf,
+
+        /// Playback speed, e.g. 10x
+        #[arg(long, default_value = "1x", value_parser = recording::parse_speed)]
+        speed: f64,
+    },
+    /// Run past trades through the alert checks and record the alerts in history
+    Backfill {
+        /// Start of the period (YYYY-MM-DD or RFC 3339, UTC)
+        #[arg(long, value_name = "TIME", value_parser = parse_time)]
+        since: chrono::DateTime<chrono::Utc>,
+
+        /// End of the period (YYYY-MM-DD or RFC 3339, UTC; default: now)
+        #[arg(long, value_name = "TIME", value_parser = parse_time)]
+        until: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Platforms to backfill (comma separated; default: those enabled in the config)
+        #[arg(long, value_enum, value_delimiter = ',')]
+        platform: Vec<types::Platform>,
+
         /// Minimum transaction size to alert on (in USD)
         #[arg(short, long, default_value = "25000")]
         threshold: u64,
 
-        /// Polling interval in seconds
-        #[arg(short, long, default_value = "5")]
-        interval: u64,
+        /// Maximum pages (of 100 trades) to fetch per platform
+        #[arg(long, default_value = "100")]
+        max_pages: usize,
+
+        /// Also send the alerts to the configured webhook
+        #[arg(long)]
+        webhook: bool,
+
+        /// Repeat/heavy actor window, as for `watch` (repeatable)
+        #[arg(long, value_name = "SPEC")]
+        actor_window: Vec<actors::ActorWindow>,
     },
     /// View alert history
     History {
@@ -35,7 +255,7 @@ enum Commands {
         #[arg(short, long, default_value = "20")]
         limit: usize,
 
-        /// Filter by platform: polymarket, kalshi, or all (default: all)
+        /// Filter by platform: polymarket, kalshi, manifold, or all (default: all)
         #[arg(short, long, default_value = "all")]
         platform: String,
 
@@ -43,32 +263,171 @@ enum Commands {
         #[arg(long)]
         json: bool,
     },
+    /// Show what a wallet holds, as booked by `watch --persist-wallets`
+    Positions {
+        /// Wallet address or trader ID
+        wallet: String,
+
+        /// Platform the wallet trades on
+        #[arg(short, long, value_enum, default_value = "polymarket")]
+        platform: types::Platform,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Show a wallet's tracked activity, alerts, positions and record
+    Wallet {
+        /// Wallet address or trader ID
+        address: String,
+
+        /// Platform the wallet trades on (positions and record are only looked up on Polymarket)
+        #[arg(short, long, value_enum, default_value = "polymarket")]
+        platform: types::Platform,
+
+        /// Pages (of 100) of the wallet's recent activity to work out its record and top markets from
+        #[arg(long, default_value = "5")]
+        max_pages: usize,
+
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Manage the wallets whose trades always alert
+    Wallets {
+        #[command(subcommand)]
+        command: WalletsCommand,
+    },
     /// Configure API credentials
     Setup,
     /// Show current configuration
     Status,
+    /// Show Kalshi balance, recent fills and open orders (requires API credentials)
+    Portfolio {
+        /// Number of fills and orders to show
+        #[arg(short, long, default_value = "10")]
+        limit: usize,
+    },
     /// Test alert sound
     TestSound,
     /// Test webhook notification
     TestWebhook,
 }
 
+#[derive(Subcommand)]
+enum WalletsCommand {
+    /// Watch a wallet, or change one already watched
+    Add {
+        /// Wallet address or trader ID
+        address: String,
+
+        /// Name shown in alerts
+        #[arg(short, long)]
+        label: Option<String>,
+
+        /// Tags (comma separated)
+        #[arg(long, value_delimiter = ',')]
+        tags: Vec<String>,
+
+        /// Only alert on this wallet's trades from this size (in USD; default: every trade)
+        #[arg(short, long)]
+        threshold: Option<u64>,
+    },
+    /// Stop watching a wallet
+    Remove {
+        /// Wallet address or trader ID
+        address: String,
+    },
+    /// List the watched wallets
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
+    let overrides = config::EndpointOverrides::from(cli.endpoints);
 
     match cli.command {
         Commands::Setup => {
             setup_config().await?;
         }
         Commands::Status => {
-            show_status().await?;
+            show_status(&overrides).await?;
+        }
+        Commands::Portfolio { limit } => {
+            show_portfolio(limit, &overrides).await?;
+        }
+        Commands::Watch(args) => {
+            let feed = Feed::Live {
+                overrides: &overrides,
+                record: args.record.clone(),
+            };
+            run_watch(args, Vec::new(), feed).await?;
         }
-        Commands::Watch {
+        Commands::Follow { addresses, watch } => {
+            let feed = Feed::Live {
+                overrides: &overrides,
+                record: watch.record.clone(),
+            };
+            run_watch(watch, addresses, feed).await?;
+        }
+        Commands::Replay { dir, speed } => {
+            // Run the recorded command line again, minus the network
+            let session = recording::Session::load(&dir)?;
+            let recorded = Cli::try_parse_from(
+                std::iter::once("wwatcher".to_string()).chain(session.args.iter().cloned()),
+            )?;
+            let (args, followed) = match recorded.command {
+                Commands::Watch(args) => (args, Vec::new()),
+                Commands::Follow { addresses, watch } => (watch, addresses),
+                _ => {
+                    return Err(format!(
+                        "{} was not recorded by `wwatcher watch` or `wwatcher follow`",
+                        dir.display()
+                    )
+                    .into())
+                }
+            };
+
+            let player = Arc::new(recording::Player::load(&dir, speed)?);
+            run_watch(
+                args,
+                followed,
+                Feed::Replay {
+                    session,
+                    player,
+                    speed,
+                },
+            )
+            .await?;
+        }
+        Commands::Backfill {
+            since,
+            until,
+            platform,
             threshold,
-            interval,
+            max_pages,
+            webhook,
+            actor_window,
         } => {
-            watch_whales(threshold, interval).await?;
+            let until = until.unwrap_or_else(chrono::Utc::now);
+            let alerting = AlertOptions {
+                threshold,
+                actor_windows: actor_window,
+            };
+            backfill(
+                since..until,
+                platform,
+                alerting,
+                max_pages,
+                webhook,
+                &overrides,
+            )
+            .await?;
         }
         Commands::History {
             limit,
@@ -77,12 +436,30 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         } => {
             show_alert_history(limit, &platform, json)?;
         }
+        Commands::Positions {
+            wallet,
+            platform,
+            json,
+        } => {
+            show_positions(&wallet, platform, json)?;
+        }
         Commands::TestSound => {
             test_sound().await?;
         }
         Commands::TestWebhook => {
             test_webhook().await?;
         }
+        Commands::Wallet {
+            address,
+            platform,
+            max_pages,
+            json,
+        } => {
+            show_wallet_profile(&address, platform, max_pages, json, &overrides).await?;
+        }
+        Commands::Wallets { command } => {
+            manage_wallets(command)?;
+        }
     }
 
     Ok(())
@@ -109,7 +486,7 @@ async fn setup_config() -> Result<(), Box<dyn std::error::Error>> {
     let kalshi_key_id = kalshi_key_id.trim().to_string();
 
     let kalshi_private_key = if !kalshi_key_id.is_empty() {
-        print!("Enter Kalshi Private Key: ");
+        print!("Enter path to Kalshi private key (.pem): ");
         io::stdout().flush()?;
         let mut key = String::new();
         std::io::stdin().read_line(&mut key)?;
@@ -139,23 +516,22 @@ async fn setup_config() -> Result<(), Box<dyn std::error::Error>> {
 
     println!();
 
-    // Save configuration
-    let config = config::Config {
-        kalshi_api_key_id: if kalshi_key_id.is_empty() {
-            None
-        } else {
-            Some(kalshi_key_id)
-        },
-        kalshi_private_key: if kalshi_private_key.is_empty() {
-            None
-        } else {
-            Some(kalshi_private_key)
-        },
-        webhook_url: if webhook_url.is_empty() {
-            None
-        } else {
-            Some(webhook_url)
-        },
+    // Save configuration, keeping any settings not covered by the wizard
+    let mut config = config::load_config().unwrap_or_default();
+    config.kalshi_api_key_id = if kalshi_key_id.is_empty() {
+        None
+    } else {
+        Some(kalshi_key_id)
+    };
+    config.kalshi_private_key = if kalshi_private_key.is_empty() {
+        None
+    } else {
+        Some(kalshi_private_key)
+    };
+    config.webhook_url = if webhook_url.is_empty() {
+        None
+    } else {
+        Some(webhook_url)
     };
 
     config::save_config(&config)?;
@@ -210,7 +586,7 @@ async fn test_webhook() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let webhook_url = match config.webhook_url {
+    let webhook_url = match config.webhook_url.clone() {
         Some(url) => url,
         None => {
             println!(
@@ -224,57 +600,133 @@ async fn test_webhook() -> Result<(), Box<dyn std::error::Error>> {
     println!("Sending test alert to: {}", webhook_url.bright_green());
     println!();
 
+    let http = http::HttpClient::webhook(&http::HttpSettings::from_config(&config));
+
     // Create a test alert
     let test_activity = types::WalletActivity {
         transactions_last_hour: 2,
         transactions_last_day: 5,
-        total_value_hour: 125000.0,
-        total_value_day: 380000.0,
+        total_value_hour: dec!(125000),
+        total_value_day: dec!(380000),
         is_repeat_actor: true,
         is_heavy_actor: true,
+        // The default 1h and 24h windows
+        windows: actors::default_windows()
+            .into_iter()
+            .zip([(2, dec!(125000)), (5, dec!(380000))])
+            .map(|(window, (transactions, volume))| types::WindowActivity {
+                window,
+                transactions,
+                volume,
+            })
+            .collect(),
+    };
+    let entry_exposure = types::Exposure {
+        outcome: Some("Yes".to_string()),
+        contracts: dec!(76923.08),
+        probability: dec!(0.65),
+    };
+    let exit_exposure = types::Exposure {
+        outcome: Some("Yes".to_string()),
+        contracts: dec!(-64814.81),
+        probability: dec!(0.54),
+    };
+    let test_wallet = watchlist::WatchedWallet {
+        address: "0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb".to_string(),
+        label: Some("Example Fund".to_string()),
+        tags: vec!["fund".to_string()],
+        threshold: None,
+    };
+    let entry_holding = positions::Holding {
+        market_id: "0xtest".to_string(),
+        outcome_id: "0xtest-yes".to_string(),
+        outcome: Some("Yes".to_string()),
+        market_title: Some("Will Bitcoin reach $100k by end of 2026?".to_string()),
+        contracts: dec!(76923.08),
+        cost_basis: dec!(50000),
+        realized_pnl: Decimal::ZERO,
+        mark_price: dec!(0.65),
+        payout: Decimal::ONE,
+        updated: chrono::Utc::now().timestamp(),
+    };
+    // What's left after selling 64814.81 shares at 0.54
+    let exit_holding = positions::Holding {
+        contracts: dec!(12108.27),
+        cost_basis: dec!(7870.38),
+        realized_pnl: dec!(-7129.62),
+        mark_price: dec!(0.54),
+        ..entry_holding.clone()
     };
 
     // Test BUY alert
     send_webhook_alert(
+        &http,
         &webhook_url,
         WebhookAlert {
-            platform: "Polymarket",
+            platform: types::Platform::Polymarket,
             market_title: Some("Will Bitcoin reach $100k by end of 2026?"),
             outcome: Some("Yes"),
-            side: "BUY",
-            value: 50000.0,
-            price: 0.65,
-            size: 76923.08,
-            timestamp: &chrono::Utc::now().to_rfc3339(),
+            side: types::Side::Buy,
+            kind: types::TradeKind::Trade,
+            value: dec!(50000),
+            cost: None,
+            max_payout: Some(dec!(76923.08)),
+            price: dec!(0.65),
+            size: dec!(76923.08),
+            timestamp: chrono::Utc::now().to_rfc3339(),
             wallet_id: Some("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb"),
             wallet_activity: Some(&test_activity),
+            market_details: None,
+            exposure: Some(&entry_exposure),
+            position: Some(types::PositionChange {
+                before: Decimal::ZERO,
+                after: dec!(76923.08),
+            }),
+            holding: Some(&entry_holding),
+            watched_wallet: Some(&test_wallet),
+            exit: false,
+            backfilled: false,
         },
     )
     .await;
 
     println!("Test BUY alert sent!");
-    
-    // Test SELL alert
+
+    // Test exit alert: the same wallet selling most of its shares
     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
-    
+
     send_webhook_alert(
+        &http,
         &webhook_url,
         WebhookAlert {
-            platform: "Kalshi",
-            market_title: Some("Bitcoin price on Jan 16, 2026?"),
-            outcome: Some("Bitcoin (BTC) price < $96999.99 at expiry"),
-            side: "SELL",
-            value: 35000.0,
-            price: 0.54,
-            size: 64814.81,
-            timestamp: &chrono::Utc::now().to_rfc3339(),
-            wallet_id: None,
-            wallet_activity: None,
+            platform: types::Platform::Polymarket,
+            market_title: Some("Will Bitcoin reach $100k by end of 2026?"),
+            outcome: Some("Yes"),
+            side: types::Side::Sell,
+            kind: types::TradeKind::Trade,
+            value: dec!(35000),
+            cost: None,
+            max_payout: None,
+            price: dec!(0.54),
+            size: dec!(64814.81),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            wallet_id: Some("0x742d35Cc6634C0532925a3b844Bc9e7595f0bEb"),
+            wallet_activity: Some(&test_activity),
+            market_details: None,
+            exposure: Some(&exit_exposure),
+            position: Some(types::PositionChange {
+                before: dec!(76923.08),
+                after: dec!(12108.27),
+            }),
+            holding: Some(&exit_holding),
+            watched_wallet: Some(&test_wallet),
+            exit: true,
+            backfilled: false,
         },
     )
     .await;
 
-    println!("Test SELL alert sent!");
+    println!("Test exit alert sent!");
     println!();
     println!("{}", "Test webhooks sent!".bright_green());
     println!("Check your n8n workflow to see if it received the data.");
@@ -284,7 +736,7 @@ async fn test_webhook() -> Result<(), Box<dyn std::error::Error>> {
     println!("    - alert_type: WHALE_ENTRY");
     println!("    - action: BUY");
     println!("    - value: $50,000");
-    println!("  Test 2 - Kalshi SELL:");
+    println!("  Test 2 - Polymarket SELL of a position bought earlier:");
     println!("    - alert_type: WHALE_EXIT");
     println!("    - action: SELL");
     println!("    - value: $35,000");
@@ -292,12 +744,17 @@ async fn test_webhook() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn show_status() -> Result<(), Box<dyn std::error::Error>> {
+async fn show_status(
+    overrides: &config::EndpointOverrides,
+) -> Result<(), Box<dyn std::error::Error>> {
     println!("{}", "WHALE WATCHER STATUS".bright_cyan().bold());
     println!();
 
     match config::load_config() {
         Ok(cfg) => {
+            let endpoints = config::Endpoints::resolve(&cfg, overrides);
+            let http = http::HttpClient::new(http::HttpSettings::from_config(&cfg));
+
             println!("Configuration:");
             println!(
                 "  Kalshi API: {}",
@@ -307,18 +764,44 @@ async fn show_status() -> Result<(), Box<dyn std::error::Error>> {
                     "Not configured (using public data)".yellow()
                 }
             );
+            if cfg.kalshi_api_key_id.is_some() {
+                match kalshi::KalshiSigner::from_config(&cfg) {
+                    Ok(Some(signer)) => {
+                        match kalshi::fetch_balance(&http, &endpoints.kalshi_api, &signer).await {
+                            Ok(_) => println!("  Kalshi Credentials: {}", "Valid".green()),
+                            Err(e) => println!(
+                                "  Kalshi Credentials: {}",
+                                format!("Invalid ({})", e).red()
+                            ),
+                        }
+                    }
+                    Ok(None) => {
+                        println!("  Kalshi Credentials: {}", "Private key missing".yellow())
+                    }
+                    Err(e) => println!("  Kalshi Credentials: {}", e.to_string().red()),
+                }
+            }
             println!(
                 "  Polymarket API: {}",
                 "Public access (no key needed)".green()
             );
             println!(
                 "  Webhook: {}",
-                if cfg.webhook_url.is_some() {
-                    format!("Configured ({})", cfg.webhook_url.as_ref().unwrap()).green()
+                if let Some(ref url) = cfg.webhook_url {
+                    format!("Configured ({})", url).green()
                 } else {
                     "Not configured".yellow()
                 }
             );
+            println!();
+            println!("Endpoints:");
+            println!("  Kalshi Environment: {}", endpoints.kalshi_env);
+            println!("  Kalshi API:         {}", endpoints.kalshi_api);
+            println!("  Kalshi WebSocket:   {}", endpoints.kalshi_ws);
+            println!("  Polymarket Data:    {}", endpoints.polymarket_data_api);
+            println!("  Polymarket Gamma:   {}", endpoints.polymarket_gamma_api);
+            println!("  Polymarket WS:      {}", endpoints.polymarket_ws);
+            println!("  Manifold API:       {}", endpoints.manifold_api);
         }
         Err(_) => {
             println!("No configuration found. Run 'wwatcher setup' to configure.");
@@ -328,342 +811,1485 @@ async fn show_status() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
-async fn watch_whales(threshold: u64, interval: u64) -> Result<(), Box<dyn std::error::Error>> {
-    // Display disclaimer
-    println!("{}", "=".repeat(70).bright_yellow());
-    println!("{}", "DISCLAIMER".bright_yellow().bold());
-    println!("This tool is for informational and research purposes only.");
-    println!("I do not condone gambling or speculative trading.");
-    println!("Use this data solely for informed decision-making and market analysis.");
-    println!("Trade responsibly and within your means.");
-    println!("{}", "=".repeat(70).bright_yellow());
+async fn show_portfolio(
+    limit: usize,
+    overrides: &config::EndpointOverrides,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", "KALSHI PORTFOLIO".bright_cyan().bold());
     println!();
 
-    println!("{}", "WHALE WATCHER ACTIVE".bright_cyan().bold());
+    let config = config::load_config()?;
+    let endpoints = config::Endpoints::resolve(&config, overrides);
+    let api_url = endpoints.kalshi_api.as_str();
+    let http = http::HttpClient::new(http::HttpSettings::from_config(&config));
+    let signer = match kalshi::KalshiSigner::from_config(&config)? {
+        Some(signer) => signer,
+        None => {
+            println!(
+                "{}",
+                "Kalshi API credentials not configured. Run 'wwatcher setup' first.".red()
+            );
+            return Ok(());
+        }
+    };
+
+    let balance = kalshi::fetch_balance(&http, api_url, &signer).await?;
     println!(
-        "Threshold: {}",
-        format!("${}", format_number(threshold)).bright_green()
+        "Balance:         {}",
+        format!("${:.2}", Decimal::new(balance.balance, 2)).bright_green()
     );
-    println!("Interval:  {} seconds", interval);
-
-    // Load config (optional credentials)
-    let config = config::load_config().ok();
-
-    if let Some(ref cfg) = config {
-        if cfg.webhook_url.is_some() {
-            println!("Webhook:   {}", "Enabled".bright_green());
-        }
+    if let Some(value) = balance.portfolio_value {
+        println!("Portfolio Value: ${:.2}", Decimal::new(value, 2));
     }
+    println!();
 
+    let fills = kalshi::fetch_fills(&http, api_url, &signer, limit).await?;
+    println!("{}", "RECENT FILLS".dimmed());
+    if fills.is_empty() {
+        println!("  No fills.");
+    }
+    for fill in &fills {
+        println!(
+            "  {} {} {} x{} @ {} ({})  {}",
+            fill.action.to_uppercase(),
+            fill.side.to_uppercase(),
+            fill.ticker,
+            fill.contracts().unwrap_or_default().normalize(),
+            format_cents(fill.price()),
+            if fill.is_taker { "taker" } else { "maker" },
+            fill.created_time.dimmed()
+        );
+    }
     println!();
 
-    let mut last_polymarket_trade_id: Option<String> = None;
-    let mut last_kalshi_trade_id: Option<String> = None;
+    let orders = kalshi::fetch_orders(&http, api_url, &signer, "resting", limit).await?;
+    println!("{}", "OPEN ORDERS".dimmed());
+    if orders.is_empty() {
+        println!("  No open orders.");
+    }
+    for order in &orders {
+        println!(
+            "  {} {} {} x{} @ {}  [{}] {}",
+            order.action.to_uppercase(),
+            order.side.to_uppercase(),
+            order.ticker,
+            order.remaining().unwrap_or_default().normalize(),
+            format_cents(order.price()),
+            order.status,
+            order.order_id.dimmed()
+        );
+    }
 
-    // Initialize wallet tracker
-    let mut wallet_tracker = types::WalletTracker::new();
+    Ok(())
+}
 
-    let mut tick_interval = time::interval(Duration::from_secs(interval));
+// A Kalshi price in dollars shown in cents, e.g. "36c" or "36.5c"
+fn format_cents(price: Option<Decimal>) -> String {
+    match price {
+        Some(price) => format!("{}c", (price * dec!(100)).normalize()),
+        None => "-".to_string(),
+    }
+}
 
-    loop {
-        tick_interval.tick().await;
-
-        // Check Polymarket
-        match polymarket::fetch_recent_trades().await {
-            Ok(mut trades) => {
-                // Update last seen trade ID first
-                if let Some(first_trade) = trades.first() {
-                    let new_last_id = first_trade.id.clone();
-
-                    for trade in &mut trades {
-                        // Skip if we've already seen this trade
-                        if let Some(ref last_id) = last_polymarket_trade_id {
-                            if trade.id == *last_id {
-                                break;
-                            }
-                        }
+/// Shared state for the platform watchers: configuration and the API clients.
+struct WatchContext {
+    config: Option<config::Config>,
+    endpoints: config::Endpoints,
+    kalshi_signer: Option<Arc<kalshi::KalshiSigner>>,
+    kalshi_http: http::HttpClient,
+    polymarket_http: http::HttpClient,
+    manifold_http: http::HttpClient,
+    webhook_http: http::HttpClient,
+    max_pages: usize,
+    feed_degraded_after: u32,
+    /// Playing back a recording: alerts are only printed
+    replaying: bool,
+}
 
-                        let trade_value = trade.size * trade.price;
-                        if trade_value >= threshold as f64 {
-                            // Market details are now included in the API response
-                            // No need for extra fetch
-
-                            // Track wallet activity
-                            let wallet_activity = if let Some(ref wallet_id) = trade.wallet_id {
-                                wallet_tracker.record_transaction(wallet_id, trade_value);
-                                Some(wallet_tracker.get_activity(wallet_id))
-                            } else {
-                                None
-                            };
-
-                            print_whale_alert(
-                                "Polymarket",
-                                trade,
-                                trade_value,
-                                wallet_activity.as_ref(),
-                            );
-
-                            // Log alert to history file
-                            let alert_data = WebhookAlert {
-                                platform: "Polymarket",
-                                market_title: trade.market_title.as_deref(),
-                                outcome: trade.outcome.as_deref(),
-                                side: &trade.side,
-                                value: trade_value,
-                                price: trade.price,
-                                size: trade.size,
-                                timestamp: &trade.timestamp,
-                                wallet_id: trade.wallet_id.as_deref(),
-                                wallet_activity: wallet_activity.as_ref(),
-                            };
-                            
-                            log_alert(&alert_data);
-
-                            // Send webhook notification
-                            if let Some(ref cfg) = config {
-                                if let Some(ref webhook_url) = cfg.webhook_url {
-                                    send_webhook_alert(webhook_url, alert_data).await;
-                                }
-                            }
-                        }
-                    }
+impl WatchContext {
+    fn new(
+        config: Option<config::Config>,
+        endpoints: config::Endpoints,
+        kalshi_signer: Option<Arc<kalshi::KalshiSigner>>,
+        max_pages: usize,
+        tap: Option<http::Tap>,
+    ) -> Self {
+        let settings = http::HttpSettings::from_config(&config.clone().unwrap_or_default());
+        let mut shared = http::HttpClient::new(settings.clone());
+        let replaying = matches!(tap, Some(http::Tap::Replay(_)));
+        if let Some(tap) = tap {
+            shared = shared.with_tap(tap);
+        }
+        let feed_degraded_after = config
+            .as_ref()
+            .and_then(|c| c.feed_degraded_after_polls)
+            .unwrap_or(types::DEFAULT_FEED_DEGRADED_AFTER);
+
+        Self {
+            config,
+            endpoints,
+            kalshi_signer,
+            kalshi_http: shared.with_budget(settings.kalshi_requests_per_minute),
+            polymarket_http: shared.with_budget(settings.polymarket_requests_per_minute),
+            manifold_http: shared.with_budget(settings.manifold_requests_per_minute),
+            webhook_http: http::HttpClient::webhook(&settings),
+            max_pages,
+            feed_degraded_after,
+            replaying,
+        }
+    }
 
-                    last_polymarket_trade_id = Some(new_last_id);
-                }
-            }
-            Err(e) => {
-                eprintln!("{} {}", "[ERROR] Polymarket:".red(), e);
-            }
+    fn webhook_url(&self) -> Option<&str> {
+        if self.replaying {
+            return None;
         }
+        self.config.as_ref().and_then(|c| c.webhook_url.as_deref())
+    }
+}
 
-        // Check Kalshi
-        match kalshi::fetch_recent_trades(config.as_ref()).await {
-            Ok(mut trades) => {
-                // Update last seen trade ID first
-                if let Some(first_trade) = trades.first() {
-                    let new_last_id = first_trade.trade_id.clone();
+/// What makes a trade or a wallet worth alerting on.
+struct AlertOptions {
+    threshold: u64,
+    /// Actor windows from the command line; empty to use the config file's
+    actor_windows: Vec<actors::ActorWindow>,
+}
 
-                    for trade in &mut trades {
-                        // Skip if we've already seen this trade
-                        if let Some(ref last_id) = last_kalshi_trade_id {
-                            if trade.trade_id == *last_id {
-                                break;
-                            }
-                        }
+/// State to keep on disk between runs.
+struct PersistOptions {
+    seen: bool,
+    markets: bool,
+    wallets: bool,
+}
 
-                        // Kalshi prices are in cents, count is number of contracts
-                        let trade_value = (trade.yes_price / 100.0) * f64::from(trade.count);
-                        if trade_value >= threshold as f64 {
-                            // Fetch market details
-                            if let Some(title) = kalshi::fetch_market_info(&trade.ticker).await {
-                                trade.market_title = Some(title);
-                            }
-                            
-                            // Extract outcome from ticker with the side they're taking
-                            let outcome = kalshi::parse_ticker_details(&trade.ticker, &trade.taker_side);
-                            
-                            // Use the actual taker_side from the trade
-                            let action = trade.taker_side.to_uppercase();
-                            
-                            // Note: Kalshi doesn't expose wallet IDs in public API
-                            print_kalshi_alert(trade, trade_value, None);
-
-                            // Log alert to history file
-                            let alert_data = WebhookAlert {
-                                platform: "Kalshi",
-                                market_title: trade.market_title.as_deref(),
-                                outcome: Some(&outcome),
-                                side: &action,
-                                value: trade_value,
-                                price: trade.yes_price / 100.0,
-                                size: f64::from(trade.count),
-                                timestamp: &trade.created_time,
-                                wallet_id: None,
-                                wallet_activity: None,
-                            };
-                            
-                            log_alert(&alert_data);
-
-                            // Send webhook notification
-                            if let Some(ref cfg) = config {
-                                if let Some(ref webhook_url) = cfg.webhook_url {
-                                    send_webhook_alert(webhook_url, alert_data).await;
-                                }
-                            }
-                        }
-                    }
+struct PlatformOptions {
+    platforms: Vec<types::Platform>,
+    polymarket_interval: Option<u64>,
+    kalshi_interval: Option<u64>,
+    manifold_interval: Option<u64>,
+    /// Markets picked on the command line
+    markets: Vec<watchlist::WatchedMarket>,
+    /// Also pick the config file's watchlist
+    watchlist_only: bool,
+    /// Polymarket wallets whose activity is polled directly
+    followed: Vec<String>,
+}
 
-                    last_kalshi_trade_id = Some(new_last_id);
-                }
-            }
-            Err(e) => {
-                eprintln!("{} {}", "[ERROR] Kalshi:".red(), e);
-            }
-        }
-    }
+/// Where the watcher's API responses come from.
+enum Feed<'a> {
+    /// The live APIs, optionally recording every response to a directory
+    Live {
+        overrides: &'a config::EndpointOverrides,
+        record: Option<std::path::PathBuf>,
+    },
+    /// A recording, played back `speed` times faster than it was made
+    Replay {
+        session: recording::Session,
+        player: Arc<recording::Player>,
+        speed: f64,
+    },
 }
 
-fn print_whale_alert(
-    platform: &str,
-    trade: &polymarket::Trade,
-    value: f64,
-    wallet_activity: Option<&types::WalletActivity>,
-) {
-    let is_sell = trade.side.to_uppercase() == "SELL";
+struct StreamOptions {
+    kalshi_stream: bool,
+    polymarket_stream: bool,
+    polymarket_markets: Vec<String>,
+}
 
-    // Enhanced alert sound for repeat actors or sells
-    if let Some(activity) = wallet_activity {
-        if activity.is_repeat_actor || activity.is_heavy_actor {
-            // Triple beep for repeat/heavy actors
-            play_alert_sound();
-            std::thread::sleep(std::time::Duration::from_millis(100));
-            play_alert_sound();
-            std::thread::sleep(std::time::Duration::from_millis(100));
-            play_alert_sound();
-        } else {
-            play_alert_sound();
+/// What the platform watchers send to the reporter.
+enum WatchEvent {
+    Trade(Box<Alert>),
+    Feed(types::FeedEvent),
+}
+
+/// A trade over the threshold.
+struct Alert {
+    trade: types::NormalizedTrade,
+    wallet_activity: Option<types::WalletActivity>,
+    /// The trader's position in the market before and after the trade
+    position: Option<types::PositionChange>,
+    /// The trader's holding in the outcome traded, after the trade
+    holding: Option<positions::Holding>,
+    /// The trader is in the config file's `wallets`
+    watched_wallet: Option<watchlist::WatchedWallet>,
+    /// Found by `wwatcher backfill` rather than while watching
+    backfilled: bool,
+}
+
+impl Alert {
+    /// Whether the trader cut back a position they built earlier.
+    fn is_exit(&self) -> bool {
+        self.position.is_some_and(|p| p.is_exit())
+    }
+
+    fn webhook_alert(&self) -> WebhookAlert<'_> {
+        let trade = &self.trade;
+        WebhookAlert {
+            platform: trade.platform,
+            market_title: trade.market_title.as_deref(),
+            outcome: trade.outcome.as_deref(),
+            side: trade.side,
+            kind: trade.kind,
+            value: trade.notional,
+            cost: trade.cost,
+            max_payout: trade.max_payout,
+            price: trade.price,
+            size: trade.contracts,
+            timestamp: trade.timestamp.to_rfc3339(),
+            wallet_id: trade.actor_id.as_deref(),
+            wallet_activity: self.wallet_activity.as_ref(),
+            market_details: trade.market.as_ref(),
+            exposure: trade.exposure.as_ref(),
+            position: self.position,
+            holding: self.holding.as_ref(),
+            watched_wallet: self.watched_wallet.as_ref(),
+            exit: self.is_exit(),
+            backfilled: self.backfilled,
         }
-    } else {
-        play_alert_sound();
     }
+}
 
-    println!();
+// Turn the `watch` flags into the watcher's options
+async fn run_watch(
+    args: WatchArgs,
+    followed: Vec<String>,
+    feed: Feed<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let WatchArgs {
+        threshold,
+        interval,
+        max_pages,
+        persist_seen,
+        persist_markets,
+        persist_wallets,
+        kalshi_stream,
+        polymarket_stream,
+        polymarket_markets,
+        platforms,
+        polymarket_interval,
+        kalshi_interval,
+        manifold_interval,
+        market,
+        ticker,
+        series,
+        event,
+        watchlist,
+        record: _,
+        actor_window,
+    } = args;
+
+    // Each flag value becomes its own watchlist-style entry
+    let markets = market
+        .into_iter()
+        .map(|id| watchlist::WatchedMarket {
+            market: Some(id),
+            ..Default::default()
+        })
+        .chain(ticker.into_iter().map(|id| watchlist::WatchedMarket {
+            ticker: Some(id),
+            ..Default::default()
+        }))
+        .chain(series.into_iter().map(|id| watchlist::WatchedMarket {
+            series: Some(id),
+            ..Default::default()
+        }))
+        .chain(event.into_iter().map(|id| watchlist::WatchedMarket {
+            event: Some(id),
+            ..Default::default()
+        }))
+        .collect();
 
-    // Enhanced header for repeat actors or exits
-    let header = if is_sell {
-        if let Some(activity) = wallet_activity {
-            if activity.is_heavy_actor {
-                format!("[HIGH PRIORITY] WHALE EXITING POSITION - {}", platform)
-            } else if activity.is_repeat_actor {
-                format!("[ELEVATED ALERT] WHALE EXITING POSITION - {}", platform)
-            } else {
-                format!("[ALERT] WHALE EXITING POSITION - {}", platform)
+    watch_whales(
+        AlertOptions {
+            threshold,
+            actor_windows: actor_window,
+        },
+        interval,
+        max_pages,
+        PersistOptions {
+            seen: persist_seen,
+            markets: persist_markets,
+            wallets: persist_wallets,
+        },
+        PlatformOptions {
+            platforms,
+            polymarket_interval,
+            kalshi_interval,
+            manifold_interval,
+            markets,
+            watchlist_only: watchlist,
+            followed,
+        },
+        StreamOptions {
+            kalshi_stream,
+            polymarket_stream,
+            polymarket_markets,
+        },
+        feed,
+    )
+    .await
+}
+
+async fn watch_whales(
+    alerting: AlertOptions,
+    interval: u64,
+    max_pages: usize,
+    persist: PersistOptions,
+    platforms: PlatformOptions,
+    streams: StreamOptions,
+    feed: Feed<'_>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let StreamOptions {
+        mut kalshi_stream,
+        mut polymarket_stream,
+        polymarket_markets,
+    } = streams;
+    let mut persist = persist;
+
+    // Load config (optional credentials)
+    let config = config::load_config().ok();
+    let cfg = config.clone().unwrap_or_default();
+    let threshold = alerting.threshold;
+    let actor_windows = actors::pick(alerting.actor_windows, &cfg.actor_windows);
+
+    // Responses come from the live APIs or from a recording
+    let (endpoints, tap, speed, feed_note) = match feed {
+        Feed::Live { overrides, record } => {
+            let endpoints = config::Endpoints::resolve(&cfg, overrides);
+            match record {
+                Some(dir) => {
+                    let session = recording::Session {
+                        started_at: chrono::Utc::now(),
+                        args: std::env::args().skip(1).collect(),
+                        endpoints: endpoints.clone(),
+                    };
+                    let recorder = recording::Recorder::create(&dir, &session)?;
+                    let note = format!("Recording: {}", dir.display());
+                    (
+                        endpoints,
+                        Some(http::Tap::Record(Arc::new(recorder))),
+                        1.0,
+                        Some(note),
+                    )
+                }
+                None => (endpoints, None, 1.0, None),
             }
-        } else {
-            format!("[ALERT] WHALE EXITING POSITION - {}", platform)
         }
-    } else if let Some(activity) = wallet_activity {
-        if activity.is_heavy_actor {
-            format!("[HIGH PRIORITY ALERT] REPEAT HEAVY ACTOR - {}", platform)
-        } else if activity.is_repeat_actor {
-            format!("[ELEVATED ALERT] REPEAT ACTOR - {}", platform)
+        Feed::Replay {
+            session,
+            player,
+            speed,
+        } => {
+            // A replay starts from nothing and leaves nothing behind, and
+            // only polled responses are in the recording
+            persist = PersistOptions {
+                seen: false,
+                markets: false,
+                wallets: false,
+            };
+            kalshi_stream = false;
+            polymarket_stream = false;
+
+            let note = format!(
+                "Replay:    session of {} at {}x",
+                session.started_at.format("%Y-%m-%d %H:%M UTC"),
+                speed
+            );
+            (
+                session.endpoints,
+                Some(http::Tap::Replay(player)),
+                speed,
+                Some(note),
+            )
+        }
+    };
+    let player = match &tap {
+        Some(http::Tap::Replay(player)) => Some(player.clone()),
+        _ => None,
+    };
+    // Polls keep the recorded cadence, sped up with the replay
+    let pace = |secs: u64| Duration::from_secs_f64(secs as f64 / speed);
+
+    // Markets picked for this run, and every market with its own threshold
+    let mut selected = platforms.markets.clone();
+    if platforms.watchlist_only {
+        if cfg.watchlist.is_empty() {
+            return Err("--watchlist given but the config file has no watchlist".into());
+        }
+        selected.extend(cfg.watchlist.iter().cloned());
+    }
+    let mut watched = cfg.watchlist.clone();
+    watched.extend(platforms.markets.iter().cloned());
+
+    // Platforms given on the command line replace the config file's choice.
+    // Picking markets or following wallets limits the run to their platforms.
+    let following = !platforms.followed.is_empty();
+    let enabled = |platform: types::Platform, config_enabled: Option<bool>| {
+        if !platforms.platforms.is_empty() {
+            platforms.platforms.contains(&platform)
+        } else if !selected.is_empty() || following {
+            selected.iter().any(|w| w.platform() == platform)
+                || (following && platform == types::Platform::Polymarket)
         } else {
-            format!("[ALERT] LARGE TRANSACTION DETECTED - {}", platform)
+            config_enabled.unwrap_or(true)
         }
-    } else {
-        format!("[ALERT] LARGE TRANSACTION DETECTED - {}", platform)
     };
+    let polymarket_enabled = enabled(types::Platform::Polymarket, cfg.polymarket_enabled);
+    let kalshi_enabled = enabled(types::Platform::Kalshi, cfg.kalshi_enabled);
+    // Manifold is opt-in: its bets are in play money
+    let manifold_enabled = enabled(
+        types::Platform::Manifold,
+        Some(cfg.manifold_enabled.unwrap_or(false)),
+    );
+    if !polymarket_enabled && !kalshi_enabled && !manifold_enabled {
+        return Err("No platforms enabled; pass --platforms or set polymarket_enabled/kalshi_enabled/manifold_enabled in the config".into());
+    }
+    if following && !polymarket_enabled {
+        return Err(
+            "Followed wallets are polled on Polymarket; add polymarket to --platforms".into(),
+        );
+    }
 
-    println!("{}", header.bright_red().bold());
-    println!("{}", "=".repeat(70).dimmed());
-
-    // Display market title if available
-    if let Some(ref title) = trade.market_title {
-        println!("Question:   {}", title.bright_white().bold());
+    let polymarket_interval = platforms
+        .polymarket_interval
+        .or(cfg.polymarket_interval_secs)
+        .unwrap_or(interval);
+    let kalshi_interval = platforms
+        .kalshi_interval
+        .or(cfg.kalshi_interval_secs)
+        .unwrap_or(interval);
+    let manifold_interval = platforms
+        .manifold_interval
+        .or(cfg.manifold_interval_secs)
+        .unwrap_or(interval);
+    let mana_usd_rate = cfg
+        .manifold_mana_usd_rate
+        .unwrap_or(manifold::DEFAULT_MANA_USD_RATE);
 
-        if let Some(ref outcome) = trade.outcome {
-            let action = if trade.side.to_uppercase() == "BUY" {
-                format!("BUYING '{}' shares", outcome)
-            } else {
-                format!("SELLING '{}' shares (EXITING POSITION)", outcome)
-            };
-            let action_color = if trade.side.to_uppercase() == "SELL" {
-                action.bright_red().bold()
-            } else {
-                action.bright_yellow().bold()
-            };
-            println!("Position:   {}", action_color);
+    // Display disclaimer
+    println!("{}", "=".repeat(70).bright_yellow());
+    println!("{}", "DISCLAIMER".bright_yellow().bold());
+    println!("This tool is for informational and research purposes only.");
+    println!("I do not condone gambling or speculative trading.");
+    println!("Use this data solely for informed decision-making and market analysis.");
+    println!("Trade responsibly and within your means.");
+    println!("{}", "=".repeat(70).bright_yellow());
+    println!();
+
+    println!("{}", "WHALE WATCHER ACTIVE".bright_cyan().bold());
+    println!(
+        "Threshold: {}",
+        format!("${}", format_number(threshold)).bright_green()
+    );
+    if polymarket_enabled {
+        println!("Polymarket: every {} seconds", polymarket_interval);
+    }
+    if kalshi_enabled {
+        println!("Kalshi:    every {} seconds", kalshi_interval);
+    }
+    if manifold_enabled {
+        println!(
+            "Manifold:  every {} seconds (1 mana = ${})",
+            manifold_interval, mana_usd_rate
+        );
+    }
+    println!("Depth:     up to {} pages per poll", max_pages);
+    print_actor_windows(&actor_windows);
+    if let Some(note) = feed_note {
+        println!("{}", note.bright_cyan());
+    }
+    if matches!(tap, Some(http::Tap::Record(_))) && (kalshi_stream || polymarket_stream) {
+        eprintln!(
+            "{} Streamed trades aren't recorded; only polled responses are",
+            "[WARNING]".yellow()
+        );
+    }
+
+    // Replays don't sign requests, so they need no credentials
+    let kalshi_signer = match player {
+        Some(_) => None,
+        None => load_kalshi_signer(config.as_ref()),
+    };
+
+    if cfg.webhook_url.is_some() {
+        println!("Webhook:   {}", "Enabled".bright_green());
+    }
+
+    let ctx = Arc::new(WatchContext::new(
+        config,
+        endpoints,
+        kalshi_signer,
+        max_pages,
+        tap,
+    ));
+
+    if polymarket_enabled {
+        resolve_market_slugs(
+            &ctx,
+            selected.iter_mut().chain(watched.iter_mut()).collect(),
+        )
+        .await?;
+    }
+
+    // Followed wallets alert on everything they do, whatever threshold the
+    // config file gives them
+    let mut wallets: Vec<watchlist::WatchedWallet> = platforms
+        .followed
+        .iter()
+        .map(|address| watchlist::WatchedWallet {
+            address: address.clone(),
+            threshold: None,
+            ..cfg
+                .wallets
+                .iter()
+                .find(|w| w.matches(address))
+                .cloned()
+                .unwrap_or_default()
+        })
+        .collect();
+    wallets.extend(cfg.wallets.iter().cloned());
+    let thresholds = |platform| {
+        watchlist::Thresholds::new(threshold)
+            .with_watched(platform, &watched)
+            .with_wallets(&wallets)
+    };
+    let custom_thresholds = watched.iter().filter(|w| w.threshold.is_some()).count();
+    if custom_thresholds > 0 {
+        println!(
+            "Watchlist: {} markets with their own threshold",
+            custom_thresholds
+        );
+    }
+    if !cfg.wallets.is_empty() {
+        println!("Watching:  {} wallets", cfg.wallets.len());
+    }
+
+    if kalshi_enabled {
+        if ctx.kalshi_signer.is_some() {
+            println!("Kalshi:    {}", "Authenticated".bright_green());
+        }
+        if ctx.endpoints.kalshi_env != config::KalshiEnv::Prod {
             println!(
-                "Prediction: Market believes '{}' has {:.1}% chance",
-                outcome,
-                trade.price * 100.0
+                "Kalshi:    {} environment",
+                ctx.endpoints.kalshi_env.to_string().bright_yellow()
             );
         }
+    }
+
+    // Trades already handled, so reordered pages, stream/REST overlap and
+    // restarts don't re-alert
+    let new_seen = |file_name: &str| -> Result<seen::SeenTrades, Box<dyn std::error::Error>> {
+        Ok(if persist.seen {
+            seen::SeenTrades::persistent(
+                config::data_path(file_name)?,
+                seen::DEFAULT_TTL_SECS,
+                seen::DEFAULT_CAPACITY,
+            )
+        } else {
+            seen::SeenTrades::new(seen::DEFAULT_TTL_SECS, seen::DEFAULT_CAPACITY)
+        })
+    };
+    let seen_polymarket = new_seen("seen_polymarket.json")?;
+    let seen_kalshi = new_seen("seen_kalshi.json")?;
+    let seen_manifold = new_seen("seen_manifold.json")?;
+    if persist.seen {
+        println!(
+            "Dedupe:    {} (restored {} Polymarket, {} Kalshi, {} Manifold trades)",
+            "Persistent".bright_green(),
+            seen_polymarket.len(),
+            seen_kalshi.len(),
+            seen_manifold.len()
+        );
+    }
+
+    // Wallet activity behind the repeat and heavy actor checks
+    let new_wallets =
+        |file_name: &str| -> Result<types::WalletTracker, Box<dyn std::error::Error>> {
+            Ok(if persist.wallets {
+                types::WalletTracker::persistent(
+                    &config::data_path(file_name)?,
+                    actor_windows.clone(),
+                )?
+            } else {
+                types::WalletTracker::new(actor_windows.clone())
+            })
+        };
+    let wallets_polymarket = new_wallets("wallets_polymarket.db")?;
+    let wallets_kalshi = new_wallets("wallets_kalshi.db")?;
+    let wallets_manifold = new_wallets("wallets_manifold.db")?;
+    if persist.wallets {
+        println!(
+            "Wallets:   {} (restored {} Polymarket, {} Kalshi, {} Manifold transactions)",
+            "Persistent".bright_green(),
+            wallets_polymarket.len(),
+            wallets_kalshi.len(),
+            wallets_manifold.len()
+        );
+    }
+
+    let market_cache_ttl = cfg
+        .market_cache_ttl_secs
+        .unwrap_or(market_cache::DEFAULT_TTL_SECS);
+    let kalshi_markets = if persist.markets {
+        market_cache::MarketCache::persistent(
+            config::data_path("markets_kalshi.json")?,
+            market_cache_ttl,
+        )
+    } else {
+        market_cache::MarketCache::new(market_cache_ttl)
+    };
+    let polymarket_markets_cache = if persist.markets {
+        market_cache::MarketCache::persistent(
+            config::data_path("markets_polymarket.json")?,
+            market_cache_ttl,
+        )
     } else {
+        market_cache::MarketCache::new(market_cache_ttl)
+    };
+    let manifold_markets = if persist.markets {
+        market_cache::MarketCache::persistent(
+            config::data_path("markets_manifold.json")?,
+            market_cache_ttl,
+        )
+    } else {
+        market_cache::MarketCache::new(market_cache_ttl)
+    };
+    if persist.markets {
         println!(
-            "Market:     Unknown (ID: {})",
-            &trade.market[..20.min(trade.market.len())]
+            "Markets:   {} (restored {} Polymarket, {} Kalshi, {} Manifold markets)",
+            "Cached on disk".bright_green(),
+            polymarket_markets_cache.len(),
+            kalshi_markets.len(),
+            manifold_markets.len()
         );
     }
 
+    // Each platform runs as its own task so a slow or failing API on one
+    // side doesn't delay detection on the other
+    let (alert_tx, mut alert_rx) = tokio::sync::mpsc::channel::<WatchEvent>(256);
+
+    if polymarket_enabled {
+        let mut source = polymarket::PolymarketSource::new(
+            ctx.polymarket_http.clone(),
+            ctx.endpoints.polymarket_data_api.clone(),
+            ctx.endpoints.polymarket_gamma_api.clone(),
+            polymarket_markets_cache,
+        );
+        let condition_ids: Vec<String> = selected.iter().filter_map(|w| w.market.clone()).collect();
+        let all_markets = condition_ids.is_empty() && polymarket_markets.is_empty();
+        if !condition_ids.is_empty() {
+            println!("Polymarket markets: {}", condition_ids.join(", "));
+            source = source.with_markets(condition_ids);
+        }
+        if following {
+            println!("Following: {}", platforms.followed.join(", "));
+            source = source.with_followed(platforms.followed.clone());
+        }
+        if polymarket_stream {
+            let url = ctx.endpoints.polymarket_ws.clone();
+            println!("Polymarket: {} ({})", "Streaming".bright_green(), url);
+            if !polymarket_markets.is_empty() {
+                println!("Markets:   {}", polymarket_markets.join(", "));
+            }
+            if all_markets {
+                println!(
+                    "{} The stream covers the {} most active markets; polling continues for the rest",
+                    "[WARNING]".yellow(),
+                    polymarket::STREAM_ALL_MARKETS_LIMIT
+                );
+            }
+            source = source.with_stream(url, polymarket_markets);
+        }
+
+        tokio::spawn(watch_source(
+            ctx.clone(),
+            source,
+            pace(polymarket_interval),
+            seen_polymarket,
+            wallets_polymarket,
+            thresholds(types::Platform::Polymarket),
+            alert_tx.clone(),
+        ));
+    }
+
+    if kalshi_enabled {
+        let mut source = kalshi::KalshiSource::new(
+            ctx.kalshi_http.clone(),
+            ctx.endpoints.kalshi_api.clone(),
+            ctx.kalshi_signer.clone(),
+            kalshi_markets,
+        );
+        let scope = watchlist::KalshiScope::from_watched(&selected);
+        if !scope.is_empty() {
+            let names: Vec<&str> = scope
+                .tickers
+                .iter()
+                .chain(&scope.events)
+                .chain(&scope.series)
+                .map(String::as_str)
+                .collect();
+            println!("Kalshi markets: {}", names.join(", "));
+            source = source.with_scope(scope);
+        }
+        if kalshi_stream {
+            let url = ctx.endpoints.kalshi_ws.clone();
+            println!("Kalshi:    {} ({})", "Streaming".bright_green(), url);
+            source = source.with_stream(url);
+        }
+
+        tokio::spawn(watch_source(
+            ctx.clone(),
+            source,
+            pace(kalshi_interval),
+            seen_kalshi,
+            wallets_kalshi,
+            thresholds(types::Platform::Kalshi),
+            alert_tx.clone(),
+        ));
+    }
+
+    if manifold_enabled {
+        let source = manifold::ManifoldSource::new(
+            ctx.manifold_http.clone(),
+            ctx.endpoints.manifold_api.clone(),
+            mana_usd_rate,
+            manifold_markets,
+        );
+
+        tokio::spawn(watch_source(
+            ctx.clone(),
+            source,
+            pace(manifold_interval),
+            seen_manifold,
+            wallets_manifold,
+            thresholds(types::Platform::Manifold),
+            alert_tx.clone(),
+        ));
+    }
+
     println!();
-    println!("{}", "TRANSACTION DETAILS".dimmed());
-    println!(
-        "Amount:     {}",
-        format!("${:.2}", value).bright_yellow().bold()
-    );
-    println!("Contracts:  {:.2} @ ${:.4} each", trade.size, trade.price);
-    let action_text = if is_sell {
-        format!("{} shares", trade.side.to_uppercase()).bright_red()
-    } else {
-        format!("{} shares", trade.side.to_uppercase()).bright_magenta()
+
+    // Only the watchers hold senders now, so the loop ends if they all stop
+    drop(alert_tx);
+
+    // A replay ends once the recording has played out
+    let replay_done = async {
+        match &player {
+            Some(player) => player.finished().await,
+            None => std::future::pending().await,
+        }
     };
-    println!("Action:     {}", action_text);
-    println!("Timestamp:  {}", trade.timestamp);
+    tokio::pin!(replay_done);
 
-    // Display wallet activity if available
-    if let Some(activity) = wallet_activity {
-        if let Some(ref wallet_id) = trade.wallet_id {
-            println!();
-            println!("{}", "[WALLET ACTIVITY]".bright_cyan().bold());
-            println!(
-                "Wallet:   {}...{}",
-                &wallet_id[..8.min(wallet_id.len())],
-                if wallet_id.len() > 8 {
-                    &wallet_id[wallet_id.len() - 6..]
-                } else {
-                    ""
+    loop {
+        tokio::select! {
+            event = alert_rx.recv() => match event {
+                Some(WatchEvent::Trade(alert)) => report_alert(*alert, &ctx),
+                Some(WatchEvent::Feed(event)) => report_feed_event(event, &ctx),
+                None => break,
+            },
+            _ = &mut replay_done => {
+                println!("{}", "[REPLAY] Recording finished".bright_cyan().bold());
+                let unused = player.as_ref().map_or(0, |p| p.unused());
+                if unused > 0 {
+                    println!(
+                        "{} responses were never requested; the replay took a different path than the recording",
+                        unused
+                    );
                 }
+                return Ok(());
+            }
+        }
+    }
+
+    Err("all platform watchers stopped".into())
+}
+
+/// Fetch past trades and run them through the same checks as `watch`,
+/// logging the alerts to history marked as backfilled.
+async fn backfill(
+    period: std::ops::Range<chrono::DateTime<chrono::Utc>>,
+    platforms: Vec<types::Platform>,
+    alerting: AlertOptions,
+    max_pages: usize,
+    send_webhooks: bool,
+    overrides: &config::EndpointOverrides,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if period.start >= period.end {
+        return Err("--since must be before --until".into());
+    }
+
+    let config = config::load_config().ok();
+    let cfg = config.clone().unwrap_or_default();
+    let threshold = alerting.threshold;
+    let actor_windows = actors::pick(alerting.actor_windows, &cfg.actor_windows);
+    let endpoints = config::Endpoints::resolve(&cfg, overrides);
+
+    let platforms = if platforms.is_empty() {
+        let mut enabled = Vec::new();
+        if cfg.polymarket_enabled.unwrap_or(true) {
+            enabled.push(types::Platform::Polymarket);
+        }
+        if cfg.kalshi_enabled.unwrap_or(true) {
+            enabled.push(types::Platform::Kalshi);
+        }
+        if cfg.manifold_enabled.unwrap_or(false) {
+            enabled.push(types::Platform::Manifold);
+        }
+        enabled
+    } else {
+        platforms
+    };
+    if platforms.is_empty() {
+        return Err("No platforms enabled; pass --platform".into());
+    }
+
+    println!("{}", "WHALE WATCHER BACKFILL".bright_cyan().bold());
+    println!(
+        "Period:    {} to {}",
+        period.start.format("%Y-%m-%d %H:%M UTC"),
+        period.end.format("%Y-%m-%d %H:%M UTC")
+    );
+    println!(
+        "Threshold: {}",
+        format!("${}", format_number(threshold)).bright_green()
+    );
+    println!("Depth:     up to {} pages per platform", max_pages);
+    print_actor_windows(&actor_windows);
+
+    let kalshi_signer = load_kalshi_signer(config.as_ref());
+    let ctx = Arc::new(WatchContext::new(
+        config,
+        endpoints,
+        kalshi_signer,
+        max_pages,
+        None,
+    ));
+
+    let send_webhooks = match (send_webhooks, ctx.webhook_url()) {
+        (true, Some(_)) => {
+            println!("Webhook:   {}", "Enabled".bright_green());
+            true
+        }
+        (true, None) => {
+            eprintln!(
+                "{} --webhook given but no webhook is configured",
+                "[WARNING]".yellow()
             );
-            println!("Txns (1h):  {}", activity.transactions_last_hour);
-            println!("Txns (24h): {}", activity.transactions_last_day);
-            println!("Volume (1h):  ${:.2}", activity.total_value_hour);
-            println!("Volume (24h): ${:.2}", activity.total_value_day);
+            false
+        }
+        (false, _) => false,
+    };
+    println!();
 
-            if activity.is_heavy_actor {
-                println!(
-                    "{}",
-                    "Status: HEAVY ACTOR (5+ transactions in 24h)"
-                        .bright_red()
-                        .bold()
+    let mut watched = cfg.watchlist.clone();
+    if platforms.contains(&types::Platform::Polymarket) {
+        resolve_market_slugs(&ctx, watched.iter_mut().collect()).await?;
+    }
+
+    let market_cache_ttl = cfg
+        .market_cache_ttl_secs
+        .unwrap_or(market_cache::DEFAULT_TTL_SECS);
+    let range = (period.start.timestamp(), period.end.timestamp());
+
+    for platform in platforms {
+        let thresholds = watchlist::Thresholds::new(threshold)
+            .with_watched(platform, &watched)
+            .with_wallets(&cfg.wallets);
+        let result = match platform {
+            types::Platform::Polymarket => {
+                let source = polymarket::PolymarketSource::new(
+                    ctx.polymarket_http.clone(),
+                    ctx.endpoints.polymarket_data_api.clone(),
+                    ctx.endpoints.polymarket_gamma_api.clone(),
+                    market_cache::MarketCache::new(market_cache_ttl),
                 );
-            } else if activity.is_repeat_actor {
-                println!(
-                    "{}",
-                    "Status: REPEAT ACTOR (multiple transactions detected)"
-                        .yellow()
-                        .bold()
+                backfill_source(
+                    &ctx,
+                    source,
+                    range,
+                    &thresholds,
+                    &actor_windows,
+                    send_webhooks,
+                )
+                .await
+            }
+            types::Platform::Kalshi => {
+                let source = kalshi::KalshiSource::new(
+                    ctx.kalshi_http.clone(),
+                    ctx.endpoints.kalshi_api.clone(),
+                    ctx.kalshi_signer.clone(),
+                    market_cache::MarketCache::new(market_cache_ttl),
+                );
+                backfill_source(
+                    &ctx,
+                    source,
+                    range,
+                    &thresholds,
+                    &actor_windows,
+                    send_webhooks,
+                )
+                .await
+            }
+            types::Platform::Manifold => {
+                let source = manifold::ManifoldSource::new(
+                    ctx.manifold_http.clone(),
+                    ctx.endpoints.manifold_api.clone(),
+                    cfg.manifold_mana_usd_rate
+                        .unwrap_or(manifold::DEFAULT_MANA_USD_RATE),
+                    market_cache::MarketCache::new(market_cache_ttl),
                 );
+                backfill_source(
+                    &ctx,
+                    source,
+                    range,
+                    &thresholds,
+                    &actor_windows,
+                    send_webhooks,
+                )
+                .await
             }
+        };
+
+        if let Err(e) = result {
+            eprintln!("{} {}", format!("[ERROR] {}:", platform).red(), e);
         }
     }
 
-    // Anomaly detection
-    detect_anomalies(trade.price, trade.size, value, wallet_activity);
+    Ok(())
+}
 
-    println!("Asset ID: {}", trade.asset_id.dimmed());
-    println!("{}", "=".repeat(70).dimmed());
-    println!();
+// Backfill one platform. Alerts are reported in trade order, so wallet
+// activity reflects what the trader had done by then.
+async fn backfill_source<S: source::TradeSource>(
+    ctx: &Arc<WatchContext>,
+    mut source: S,
+    (since, until): (i64, i64),
+    thresholds: &watchlist::Thresholds,
+    actor_windows: &[actors::ActorWindow],
+    send_webhooks: bool,
+) -> Result<(), source::SourceError> {
+    let platform = source.platform();
+    let batch = source.fetch_range(since, until, ctx.max_pages).await?;
+    report_dropped(platform, &batch);
+
+    // Offset paging repeats trades when new ones arrive mid-backfill
+    let mut ids = std::collections::HashSet::new();
+    let trades: Vec<_> = batch
+        .trades
+        .into_iter()
+        .filter(|t| ids.insert(t.trade_id.clone()))
+        .collect();
+    let scanned = trades.len();
+    let oldest = trades.iter().map(|t| t.timestamp).min();
+
+    let mut wallet_tracker = types::WalletTracker::new(actor_windows.to_vec());
+    let alerts = detect_whales(trades, &mut source, thresholds, &mut wallet_tracker, true).await;
+    let alert_count = alerts.len();
+
+    for alert in alerts {
+        print_alert(&alert);
+        log_alert(&alert.webhook_alert());
+        if let (true, Some(webhook_url)) = (send_webhooks, ctx.webhook_url()) {
+            send_webhook_alert(&ctx.webhook_http, webhook_url, alert.webhook_alert()).await;
+        }
+    }
+
+    println!(
+        "{} {} trades in the period, {} alerts",
+        format!("[BACKFILL] {}:", platform).bright_cyan(),
+        scanned,
+        alert_count
+    );
+    if batch.coverage_gap {
+        let reached = oldest.map_or("no trades".to_string(), |t| t.to_rfc3339());
+        eprintln!(
+            "{} {} only reached back to {}; raise --max-pages to go further",
+            "[COVERAGE GAP]".yellow().bold(),
+            platform,
+            reached
+        );
+    }
+
+    Ok(())
 }
 
-fn print_kalshi_alert(
-    trade: &kalshi::Trade,
-    value: f64,
-    wallet_activity: Option<&types::WalletActivity>,
+fn load_kalshi_signer(config: Option<&config::Config>) -> Option<Arc<kalshi::KalshiSigner>> {
+    match config.map(kalshi::KalshiSigner::from_config) {
+        Some(Ok(signer)) => signer.map(Arc::new),
+        Some(Err(e)) => {
+            eprintln!(
+                "{} {}",
+                "[WARNING] Kalshi credentials unusable:".yellow(),
+                e
+            );
+            None
+        }
+        None => None,
+    }
+}
+
+// Polymarket markets may be given by slug; thresholds and API filters need
+// condition IDs
+async fn resolve_market_slugs(
+    ctx: &WatchContext,
+    entries: Vec<&mut watchlist::WatchedMarket>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut slugs: Vec<String> = entries.iter().filter_map(|w| w.market.clone()).collect();
+    slugs.sort();
+    slugs.dedup();
+    if slugs.is_empty() {
+        return Ok(());
+    }
+
+    let ids = polymarket::resolve_condition_ids(
+        &ctx.polymarket_http,
+        &ctx.endpoints.polymarket_gamma_api,
+        &slugs,
+    )
+    .await?;
+    let resolved: HashMap<String, String> = slugs.into_iter().zip(ids).collect();
+    for entry in entries {
+        if let Some(market) = entry.market.as_mut() {
+            *market = resolved[market.as_str()].clone();
+        }
+    }
+    Ok(())
+}
+
+/// Poll (and optionally stream) one platform, sending alerts for every new
+/// trade over the threshold.
+async fn watch_source<S: source::TradeSource>(
+    ctx: Arc<WatchContext>,
+    mut source: S,
+    interval: Duration,
+    mut seen: seen::SeenTrades,
+    mut wallet_tracker: types::WalletTracker,
+    thresholds: watchlist::Thresholds,
+    alerts: tokio::sync::mpsc::Sender<WatchEvent>,
 ) {
-    let is_sell = trade.taker_side.to_lowercase() == "sell";
-
-    // Enhanced alert sound for exits, repeat actors, or heavy actors
-    if is_sell {
-        // Triple beep for exits - they're important!
-        play_alert_sound();
-        std::thread::sleep(std::time::Duration::from_millis(100));
-        play_alert_sound();
-        std::thread::sleep(std::time::Duration::from_millis(100));
-        play_alert_sound();
-    } else if let Some(activity) = wallet_activity {
-        if activity.is_repeat_actor || activity.is_heavy_actor {
-            // Triple beep for repeat/heavy actors
+    let platform = source.platform();
+    let mut stream = source.take_stream();
+
+    // Stream trades may lack the IDs REST trades carry, so while streaming
+    // trades are also matched on their content
+    let match_content = stream.is_some();
+
+    // Newest trade time handled; polls page back until they pass it
+    let mut watermark: Option<i64> = None;
+    let mut stream_connected = false;
+
+    // Content keys and times of streamed trades that came without a wallet,
+    // for when a poll turns them up with one
+    let mut anonymous: HashMap<String, i64> = HashMap::new();
+
+    let mut health = types::FeedHealth::new(platform, ctx.feed_degraded_after);
+
+    // Polls that couldn't reach the last seen trade
+    let mut coverage_gaps: u64 = 0;
+
+    let mut tick_interval = time::interval(interval);
+
+    loop {
+        tokio::select! {
+            _ = tick_interval.tick() => {}
+            Some(event) = next_stream_event(&mut stream) => {
+                match event {
+                    types::StreamEvent::Connected => {
+                        stream_connected = true;
+                        println!("{}", format!("[STREAM] {} connected", platform).bright_green());
+                    }
+                    types::StreamEvent::Disconnected(reason) => {
+                        stream_connected = false;
+                        eprintln!(
+                            "{} {} (falling back to polling)",
+                            format!("[STREAM] {} disconnected:", platform).yellow(),
+                            reason
+                        );
+                    }
+                    types::StreamEvent::Trade(trade) => {
+                        if mark_seen(&mut seen, &trade, match_content) {
+                            advance_watermark(&mut watermark, Some(trade.timestamp.timestamp()));
+                            if trade.actor_id.is_none() {
+                                anonymous.insert(trade.content_key(), trade.timestamp.timestamp());
+                            }
+                            process_trades(vec![trade], &mut source, &thresholds, &mut wallet_tracker, &alerts).await;
+                        }
+                    }
+                }
+                continue;
+            }
+        }
+
+        // Polling is skipped while the stream is delivering every trade; after
+        // a disconnect the first poll walks back over what it missed
+        if !stream_connected || source.polls_while_streaming() {
+            // On the first poll there's nothing to walk back to, so one page is enough
+            let pages = if watermark.is_some() {
+                ctx.max_pages
+            } else {
+                1
+            };
+            let stop_at = watermark.map(|ts| ts - REORDER_WINDOW_SECS);
+
+            let result = source.fetch_trades(pages, stop_at).await;
+            let feed_event = match &result {
+                Ok(batch) => record_poll(&mut health, batch),
+                Err(e) => health.record_problem(e.to_string()),
+            };
+            if let Some(event) = feed_event {
+                let _ = alerts.send(WatchEvent::Feed(event)).await;
+            }
+
+            match result {
+                Ok(batch) => {
+                    report_dropped(platform, &batch);
+                    if batch.coverage_gap && stop_at.is_some() {
+                        coverage_gaps += 1;
+                        report_coverage_gap(platform, &batch, coverage_gaps);
+                    }
+
+                    let mut new_trades = Vec::new();
+                    let mut identified = Vec::new();
+                    for trade in batch.trades {
+                        if mark_seen(&mut seen, &trade, match_content) {
+                            advance_watermark(&mut watermark, Some(trade.timestamp.timestamp()));
+                            new_trades.push(trade);
+                        } else if trade.actor_id.is_some()
+                            && anonymous.remove(&trade.content_key()).is_some()
+                        {
+                            identified.push(trade);
+                        }
+                    }
+
+                    process_trades(
+                        new_trades,
+                        &mut source,
+                        &thresholds,
+                        &mut wallet_tracker,
+                        &alerts,
+                    )
+                    .await;
+                    process_identified(
+                        identified,
+                        &mut source,
+                        &thresholds,
+                        &mut wallet_tracker,
+                        &alerts,
+                    )
+                    .await;
+
+                    // Polls don't look back past the reorder window
+                    if let Some(watermark) = watermark {
+                        anonymous.retain(|_, ts| *ts >= watermark - REORDER_WINDOW_SECS);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("{} {}", format!("[ERROR] {}:", platform).red(), e);
+                }
+            }
+        }
+
+        if let Err(e) = seen.save() {
+            eprintln!("{} Failed to save seen trades: {}", "[WARNING]".yellow(), e);
+        }
+        if let Err(e) = source.save() {
+            eprintln!(
+                "{} Failed to save market cache: {}",
+                "[WARNING]".yellow(),
+                e
+            );
+        }
+    }
+}
+
+// Record a trade as handled; false if it already was
+fn mark_seen(
+    seen: &mut seen::SeenTrades,
+    trade: &types::NormalizedTrade,
+    match_content: bool,
+) -> bool {
+    if match_content {
+        let content_key = trade.content_key();
+        if seen.contains(&content_key) {
+            return false;
+        }
+        seen.insert(&content_key);
+    }
+    seen.insert(&trade.trade_id)
+}
+
+// How far behind the newest handled trade a poll keeps looking, to catch
+// trades the API publishes out of order
+const REORDER_WINDOW_SECS: i64 = 120;
+
+fn advance_watermark(watermark: &mut Option<i64>, ts: Option<i64>) {
+    if let Some(ts) = ts {
+        if watermark.is_none_or(|w| ts > w) {
+            *watermark = Some(ts);
+        }
+    }
+}
+
+fn report_coverage_gap<T>(
+    platform: types::Platform,
+    batch: &types::TradeBatch<T>,
+    total_gaps: u64,
+) {
+    eprintln!(
+        "{} {}: last seen trade not reached after {} pages ({} trades); older trades may have been missed (gaps this session: {})",
+        "[COVERAGE GAP]".yellow().bold(),
+        platform,
+        batch.pages,
+        batch.trades.len(),
+        total_gaps
+    );
+}
+
+fn report_dropped<T>(platform: types::Platform, batch: &types::TradeBatch<T>) {
+    if batch.dropped == 0 {
+        return;
+    }
+
+    eprintln!(
+        "{} {}: skipped {} of {} trades that didn't match the expected format, e.g. {}",
+        "[SCHEMA]".yellow().bold(),
+        platform,
+        batch.dropped,
+        batch.received,
+        batch.drop_sample.as_deref().unwrap_or("?")
+    );
+}
+
+// A poll with nothing usable in it counts against the feed's health
+fn record_poll<T>(
+    health: &mut types::FeedHealth,
+    batch: &types::TradeBatch<T>,
+) -> Option<types::FeedEvent> {
+    if batch.parsed() > 0 {
+        health.record_ok()
+    } else if batch.dropped > 0 {
+        health.record_problem(format!(
+            "none of the {} trades returned could be parsed",
+            batch.received
+        ))
+    } else {
+        health.record_problem("no trades returned".to_string())
+    }
+}
+
+// Waits on the stream if one is running, otherwise never resolves
+async fn next_stream_event<T>(
+    stream: &mut Option<tokio::sync::mpsc::Receiver<types::StreamEvent<T>>>,
+) -> Option<types::StreamEvent<T>> {
+    match stream {
+        Some(rx) => rx.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+// Catch wallets up on streamed trades a poll has found their wallets for.
+// Trades the stream already alerted on aren't alerted again; the rest are
+// alerted on if their wallet's threshold lets them through.
+async fn process_identified<S: source::TradeSource>(
+    trades: Vec<types::NormalizedTrade>,
+    source: &mut S,
+    thresholds: &watchlist::Thresholds,
+    wallet_tracker: &mut types::WalletTracker,
+    alerts: &tokio::sync::mpsc::Sender<WatchEvent>,
+) {
+    let (alerted, unalerted): (Vec<_>, Vec<_>) = trades
+        .into_iter()
+        .partition(|t| t.notional >= thresholds.for_market(&t.market_id));
+
+    for trade in &alerted {
+        record_position(trade, wallet_tracker);
+        if let Some(actor) = &trade.actor_id {
+            wallet_tracker.record_transaction(actor, trade.notional, trade.timestamp.timestamp());
+        }
+    }
+    process_trades(unalerted, source, thresholds, wallet_tracker, alerts).await;
+}
+
+// Alert on the trades over the threshold, looking up their markets in one go
+async fn process_trades<S: source::TradeSource>(
+    trades: Vec<types::NormalizedTrade>,
+    source: &mut S,
+    thresholds: &watchlist::Thresholds,
+    wallet_tracker: &mut types::WalletTracker,
+    alerts: &tokio::sync::mpsc::Sender<WatchEvent>,
+) {
+    for alert in detect_whales(trades, source, thresholds, wallet_tracker, false).await {
+        let _ = alerts.send(WatchEvent::Trade(Box::new(alert))).await;
+    }
+}
+
+/// Pick out the trades over their threshold, oldest first, with market
+/// details and the trader's activity up to each trade.
+async fn detect_whales<S: source::TradeSource>(
+    trades: Vec<types::NormalizedTrade>,
+    source: &mut S,
+    thresholds: &watchlist::Thresholds,
+    wallet_tracker: &mut types::WalletTracker,
+    backfilled: bool,
+) -> Vec<Alert> {
+    let mut trades = trades;
+    trades.sort_by_key(|t| t.timestamp);
+
+    // Every trade moves its trader's position, so follow them all; a large
+    // sale is only an exit if the position it cuts is known
+    let (mut trades, positions): (Vec<_>, Vec<_>) = trades
+        .into_iter()
+        .map(|trade| {
+            let position = record_position(&trade, wallet_tracker);
+            (trade, position)
+        })
+        .filter(|(trade, _)| trade.notional >= thresholds.for_trade(trade))
+        .unzip();
+    if trades.is_empty() {
+        return Vec::new();
+    }
+
+    source.enrich(&mut trades).await;
+
+    trades
+        .into_iter()
+        .zip(positions)
+        .map(|(trade, (position, holding))| {
+            // Track wallet activity
+            let at = trade.timestamp.timestamp();
+            let wallet_activity = trade.actor_id.as_deref().map(|actor| {
+                wallet_tracker.record_transaction(actor, trade.notional, at);
+                wallet_tracker.get_activity(actor, at)
+            });
+
+            Alert {
+                watched_wallet: thresholds.wallet(&trade).cloned(),
+                trade,
+                wallet_activity,
+                position,
+                holding,
+                backfilled,
+            }
+        })
+        .collect()
+}
+
+// Move the trader's exposure and holding by a trade
+fn record_position(
+    trade: &types::NormalizedTrade,
+    wallet_tracker: &mut types::WalletTracker,
+) -> (Option<types::PositionChange>, Option<positions::Holding>) {
+    let position = match (&trade.actor_id, &trade.exposure) {
+        (Some(actor), Some(exposure)) => Some(wallet_tracker.record_exposure(
+            actor,
+            &trade.market_id,
+            exposure.contracts,
+            trade.timestamp.timestamp(),
+        )),
+        _ => None,
+    };
+    (position, wallet_tracker.record_holding(trade))
+}
+
+// Print, log and deliver an alert. Webhooks are sent from their own task so
+// a slow endpoint doesn't hold up the console.
+fn report_alert(alert: Alert, ctx: &Arc<WatchContext>) {
+    print_alert(&alert);
+
+    // Log alert to history file; replayed alerts are already there
+    if !ctx.replaying {
+        log_alert(&alert.webhook_alert());
+    }
+
+    // Send webhook notification
+    if let Some(webhook_url) = ctx.webhook_url() {
+        let ctx = ctx.clone();
+        let webhook_url = webhook_url.to_string();
+        tokio::spawn(async move {
+            send_webhook_alert(&ctx.webhook_http, &webhook_url, alert.webhook_alert()).await;
+        });
+    }
+}
+
+fn report_feed_event(event: types::FeedEvent, ctx: &Arc<WatchContext>) {
+    use serde_json::json;
+
+    let payload = match &event {
+        types::FeedEvent::Degraded {
+            platform,
+            polls,
+            reason,
+            escalated,
+        } => {
+            let header = if *escalated {
+                "[FEED DEGRADED - ESCALATED]".bright_red().bold()
+            } else {
+                "[FEED DEGRADED]".yellow().bold()
+            };
+            eprintln!(
+                "{} {}: {} empty or failed polls in a row; alerts may be missed. Last problem: {}",
+                header, platform, polls, reason
+            );
+            if *escalated {
+                play_anomaly_sound();
+            }
+
+            json!({
+                "platform": platform.to_string(),
+                "alert_type": "FEED_DEGRADED",
+                "severity": if *escalated { "critical" } else { "warning" },
+                "consecutive_polls": polls,
+                "reason": escape_special_chars(reason),
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            })
+        }
+        types::FeedEvent::Recovered { platform, polls } => {
+            println!(
+                "{} {}: trades are coming through again after {} bad polls",
+                "[FEED RECOVERED]".bright_green().bold(),
+                platform,
+                polls
+            );
+
+            json!({
+                "platform": platform.to_string(),
+                "alert_type": "FEED_RECOVERED",
+                "consecutive_polls": polls,
+                "timestamp": chrono::Utc::now().to_rfc3339(),
+            })
+        }
+    };
+
+    if let Some(webhook_url) = ctx.webhook_url() {
+        let ctx = ctx.clone();
+        let webhook_url = webhook_url.to_string();
+        tokio::spawn(async move {
+            post_webhook(&ctx.webhook_http, &webhook_url, &payload).await;
+        });
+    }
+}
+
+// Shown at startup when the actor rules aren't the defaults
+fn print_actor_windows(windows: &[actors::ActorWindow]) {
+    if windows != actors::default_windows().as_slice() {
+        let specs: Vec<String> = windows.iter().map(|w| w.to_string()).collect();
+        println!("Actors:    {}", specs.join("; "));
+    }
+}
+
+fn print_alert(alert: &Alert) {
+    let trade = &alert.trade;
+    let wallet_activity = alert.wallet_activity.as_ref();
+    let platform = trade.platform;
+    let is_exit = alert.is_exit();
+    let is_repeat = wallet_activity.is_some_and(|a| a.is_repeat_actor || a.is_heavy_actor);
+
+    // Watched wallets have their own sound, and exits and repeat/heavy
+    // actors get a triple beep; backfills stay quiet
+    if !alert.backfilled {
+        if alert.watched_wallet.is_some() {
+            play_watched_wallet_sound();
+        } else if is_exit || is_repeat {
             play_alert_sound();
             std::thread::sleep(std::time::Duration::from_millis(100));
             play_alert_sound();
@@ -672,144 +2298,330 @@ fn print_kalshi_alert(
         } else {
             play_alert_sound();
         }
-    } else {
-        play_alert_sound();
     }
 
     println!();
 
-    // Enhanced header for exits and repeat actors
-    let header = if is_sell {
+    // Enhanced header for repeat actors or exits
+    let header = if trade.kind != types::TradeKind::Trade {
+        format!("[ALERT] WALLET {} - {}", trade.kind, platform)
+    } else if is_exit {
         if let Some(activity) = wallet_activity {
             if activity.is_heavy_actor {
-                "[HIGH PRIORITY] WHALE EXITING POSITION - Kalshi"
-                    .bright_red()
-                    .bold()
+                format!("[HIGH PRIORITY] WHALE EXITING POSITION - {}", platform)
             } else if activity.is_repeat_actor {
-                "[ELEVATED ALERT] WHALE EXITING POSITION - Kalshi"
-                    .bright_red()
-                    .bold()
+                format!("[ELEVATED ALERT] WHALE EXITING POSITION - {}", platform)
             } else {
-                "[ALERT] WHALE EXITING POSITION - Kalshi"
-                    .bright_red()
-                    .bold()
+                format!("[ALERT] WHALE EXITING POSITION - {}", platform)
             }
         } else {
-            "[ALERT] WHALE EXITING POSITION - Kalshi"
-                .bright_red()
-                .bold()
+            format!("[ALERT] WHALE EXITING POSITION - {}", platform)
         }
     } else if let Some(activity) = wallet_activity {
         if activity.is_heavy_actor {
-            "[HIGH PRIORITY ALERT] REPEAT HEAVY ACTOR - Kalshi"
-                .bright_green()
-                .bold()
+            format!("[HIGH PRIORITY ALERT] REPEAT HEAVY ACTOR - {}", platform)
         } else if activity.is_repeat_actor {
-            "[ELEVATED ALERT] REPEAT ACTOR - Kalshi"
-                .bright_green()
-                .bold()
+            format!("[ELEVATED ALERT] REPEAT ACTOR - {}", platform)
         } else {
-            "[ALERT] LARGE TRANSACTION DETECTED - Kalshi"
-                .bright_green()
-                .bold()
+            format!("[ALERT] LARGE TRANSACTION DETECTED - {}", platform)
         }
     } else {
-        "[ALERT] LARGE TRANSACTION DETECTED - Kalshi"
-            .bright_green()
-            .bold()
+        format!("[ALERT] LARGE TRANSACTION DETECTED - {}", platform)
     };
 
-    println!("{}", header);
+    let header = if alert.backfilled {
+        format!("[BACKFILL] {}", header)
+    } else {
+        header
+    };
+
+    println!("{}", header.bright_red().bold());
     println!("{}", "=".repeat(70).dimmed());
 
-    // Display market title if available
-    if let Some(ref title) = trade.market_title {
-        println!("Question:   {}", title.bright_white().bold());
+    if let Some(ref watched) = alert.watched_wallet {
+        let mut name = watched.name().to_string();
+        if !watched.tags.is_empty() {
+            name.push_str(&format!(" [{}]", watched.tags.join(", ")));
+        }
+        println!("Watched:    {}", name.bright_magenta().bold());
     }
 
-    // Parse and display what the bet means
-    let bet_details = kalshi::parse_ticker_details(&trade.ticker, &trade.taker_side);
-    let bet_color = if is_sell {
-        bet_details.bright_red().bold()
-    } else {
-        bet_details.bright_yellow().bold()
-    };
-    println!("Position:   {}", bet_color);
+    // Display market title if available
+    match trade.market_title {
+        Some(ref title) => println!("Question:   {}", title.bright_white().bold()),
+        None => println!(
+            "Market:     Unknown (ID: {})",
+            &trade.market_id[..20.min(trade.market_id.len())]
+        ),
+    }
 
-    let direction_text = if is_sell {
-        format!(
-            "{} (EXITING {} position)",
-            trade.taker_side.to_uppercase(),
-            trade.taker_side.to_uppercase()
-        )
-    } else {
-        format!(
-            "{} (buying {} outcome)",
-            trade.taker_side.to_uppercase(),
-            trade.taker_side.to_uppercase()
-        )
-    };
-    let direction_color = if is_sell {
-        direction_text.bright_red()
-    } else {
-        direction_text.bright_magenta()
-    };
-    println!("Direction:  {}", direction_color);
+    if trade.kind != types::TradeKind::Trade {
+        println!(
+            "Activity:   {}",
+            describe_activity(trade).bright_yellow().bold()
+        );
+    } else if let Some(ref outcome) = trade.outcome {
+        let verb = match trade.side {
+            types::Side::Buy => "BUYING",
+            types::Side::Sell => "SELLING",
+        };
+        let action = if is_exit {
+            format!("{} '{}' shares (EXITING POSITION)", verb, outcome)
+        } else {
+            format!("{} '{}' shares", verb, outcome)
+        };
+        let action_color = if is_exit {
+            action.bright_red().bold()
+        } else {
+            action.bright_yellow().bold()
+        };
+        println!("Position:   {}", action_color);
+        println!(
+            "Prediction: Market believes '{}' has {:.1}% chance",
+            outcome,
+            rounded(trade.price * dec!(100), 1)
+        );
+    }
+    if let Some(ref exposure) = trade.exposure {
+        let outcome = exposure.outcome.as_deref().unwrap_or("first outcome");
+        println!(
+            "Exposure:   {} '{}' at {:.1}%",
+            describe_position(exposure.contracts),
+            outcome,
+            rounded(exposure.probability * dec!(100), 1)
+        );
+        if let Some(position) = alert.position {
+            println!(
+                "Net:        {} -> {} '{}'",
+                describe_position(position.before),
+                describe_position(position.after),
+                outcome
+            );
+        }
+    }
+    if let Some(ref holding) = alert.holding {
+        println!("Holding:    {}", describe_holding(holding));
+    }
 
     println!();
     println!("{}", "TRANSACTION DETAILS".dimmed());
     println!(
         "Amount:     {}",
-        format!("${:.2}", value).bright_yellow().bold()
-    );
-    println!(
-        "Contracts:  {} @ ${:.2} avg",
-        trade.count,
-        value / trade.count as f64
+        format!("${:.2}", rounded(trade.notional, 2))
+            .bright_yellow()
+            .bold()
     );
+    if let Some(cost) = trade.cost {
+        println!(
+            "Cost:       ${:.2} (incl. ${:.2} est. fees)",
+            rounded(cost, 2),
+            rounded(cost - trade.notional, 2)
+        );
+    }
     println!(
-        "Odds:       YES: {:.1}% | NO: {:.1}%",
-        trade.yes_price, trade.no_price
+        "Contracts:  {:.2} @ ${:.4} each",
+        rounded(trade.contracts, 2),
+        rounded(trade.price, 4)
     );
-    println!("Timestamp:  {}", trade.created_time);
-    println!();
-    println!("{}", format!("Ticker: {}", trade.ticker).dimmed());
+    if let Some(payout) = trade.max_payout {
+        println!("Max payout: ${:.2} if it wins", rounded(payout, 2));
+    }
+    let action_text = if trade.kind != types::TradeKind::Trade {
+        trade.kind.to_string().bright_cyan()
+    } else if trade.side == types::Side::Sell {
+        format!("{} shares", trade.side).bright_red()
+    } else {
+        format!("{} shares", trade.side).bright_magenta()
+    };
+    println!("Action:     {}", action_text);
+    println!("Timestamp:  {}", trade.timestamp.to_rfc3339());
 
-    // Display wallet activity if available (note: Kalshi public API doesn't expose wallet IDs)
-    if let Some(activity) = wallet_activity {
+    if let Some(ref market) = trade.market {
         println!();
-        println!("{}", "[WALLET ACTIVITY]".bright_cyan().bold());
-        println!("Note: Kalshi public API doesn't expose wallet IDs, but patterns suggest:");
-        println!("Txns (1h):  {}", activity.transactions_last_hour);
-        println!("Txns (24h): {}", activity.transactions_last_day);
-        println!("Volume (1h):  ${:.2}", activity.total_value_hour);
-        println!("Volume (24h): ${:.2}", activity.total_value_day);
-
-        if activity.is_heavy_actor {
+        println!("{}", "MARKET DETAILS".dimmed());
+        if let Some(end) = market.end_time() {
             println!(
-                "{}",
-                "Status: HEAVY ACTOR (5+ transactions in 24h)"
-                    .bright_red()
-                    .bold()
+                "Closes:     {} ({})",
+                end.format("%Y-%m-%d %H:%M UTC"),
+                format_time_until(end, trade.timestamp)
             );
-        } else if activity.is_repeat_actor {
+        }
+        if let Some(ref category) = market.category {
+            println!("Category:   {}", category);
+        }
+        if !market.tags.is_empty() {
+            println!("Tags:       {}", market.tags.join(", "));
+        }
+        if let Some(liquidity) = market.liquidity {
+            println!("Liquidity:  ${}", format_number(liquidity as u64));
+        }
+        if let Some(volume) = market.volume_24h {
+            println!("Volume 24h: ${}", format_number(volume as u64));
+        }
+        if let Some(ref url) = market.url {
+            println!("Link:       {}", url.bright_blue());
+        }
+    }
+
+    // Display wallet activity if available
+    if let Some(activity) = wallet_activity {
+        if let Some(ref wallet_id) = trade.actor_id {
+            println!();
+            println!("{}", "[WALLET ACTIVITY]".bright_cyan().bold());
             println!(
-                "{}",
-                "Status: REPEAT ACTOR (multiple transactions detected)"
-                    .yellow()
-                    .bold()
+                "Wallet:   {}...{}",
+                &wallet_id[..8.min(wallet_id.len())],
+                if wallet_id.len() > 8 {
+                    &wallet_id[wallet_id.len() - 6..]
+                } else {
+                    ""
+                }
             );
+            print_window_activity(activity);
         }
     }
 
     // Anomaly detection
-    let avg_price = (trade.yes_price + trade.no_price) / 2.0;
-    detect_anomalies(avg_price / 100.0, trade.count as f64, value, wallet_activity);
+    let anomalies = detect_anomalies(trade, wallet_activity);
+    if !anomalies.is_empty() {
+        // Play distinctive anomaly sound
+        if !alert.backfilled {
+            play_anomaly_sound();
+        }
 
+        println!();
+        println!("{}", "[ANOMALY INDICATORS]".bright_red().bold());
+        for anomaly in anomalies {
+            println!("  - {}", anomaly.yellow());
+        }
+    }
+
+    println!("{}", format!("Market ID: {}", trade.market_id).dimmed());
     println!("{}", "=".repeat(70).dimmed());
     println!();
 }
 
+// E.g. "SPLIT $5000.00 into 5000.00 shares of each outcome"
+fn describe_activity(trade: &types::NormalizedTrade) -> String {
+    let (dollars, shares) = (rounded(trade.notional, 2), rounded(trade.contracts, 2));
+    match trade.kind {
+        types::TradeKind::Split => {
+            format!(
+                "SPLIT ${:.2} into {:.2} shares of each outcome",
+                dollars, shares
+            )
+        }
+        types::TradeKind::Merge => {
+            format!(
+                "MERGED {:.2} shares of each outcome into ${:.2}",
+                shares, dollars
+            )
+        }
+        types::TradeKind::Redeem => format!("REDEEMED {:.2} shares for ${:.2}", shares, dollars),
+        types::TradeKind::Trade => format!("{} {:.2} shares", trade.side, shares),
+    }
+}
+
+// Transactions and volume in each actor window, and what they make the wallet
+fn print_window_activity(activity: &types::WalletActivity) {
+    for window in &activity.windows {
+        println!(
+            "{:<12}{}",
+            format!("Txns ({}):", window.window.window),
+            window.transactions
+        );
+    }
+    for window in &activity.windows {
+        println!(
+            "{:<14}${:.2}",
+            format!("Volume ({}):", window.window.window),
+            rounded(window.volume, 2)
+        );
+    }
+
+    // The first window that flagged the wallet at its level
+    let flagged_by = |level| {
+        activity
+            .windows
+            .iter()
+            .find(|w| w.window.level == level && w.reached_transactions())
+            .map(|w| {
+                format!(
+                    "{}+ transactions in {}",
+                    w.window.transactions.unwrap_or_default(),
+                    w.window.window
+                )
+            })
+    };
+    if let Some(reason) = flagged_by(actors::ActorLevel::Heavy) {
+        println!(
+            "{}",
+            format!("Status: HEAVY ACTOR ({})", reason)
+                .bright_red()
+                .bold()
+        );
+    } else if let Some(reason) = flagged_by(actors::ActorLevel::Repeat) {
+        println!(
+            "{}",
+            format!("Status: REPEAT ACTOR ({})", reason).yellow().bold()
+        );
+    }
+}
+
+// A holding of a binary market's first outcome; short means holding the other
+fn describe_position(contracts: Decimal) -> String {
+    if contracts.is_zero() {
+        "flat".to_string()
+    } else if contracts.is_sign_negative() {
+        format!("short {:.2}", rounded(-contracts, 2))
+    } else {
+        format!("long {:.2}", rounded(contracts, 2))
+    }
+}
+
+// E.g. "$410000.00 of 'Yes' at avg 0.3800, up 22.0%"
+fn describe_holding(holding: &positions::Holding) -> String {
+    let outcome = holding.outcome.as_deref().unwrap_or(&holding.outcome_id);
+    let realized = if holding.realized_pnl.is_zero() {
+        String::new()
+    } else {
+        format!("realized {}", signed_dollars(holding.realized_pnl))
+    };
+
+    if !holding.is_open() {
+        return format!("closed '{}', {}", outcome, realized);
+    }
+
+    let mut description = format!(
+        "${:.2} of '{}' at avg {:.4}",
+        rounded(holding.market_value(), 2),
+        outcome,
+        rounded(holding.avg_price(), 4)
+    );
+    if let Some(percent) = holding.return_percent() {
+        let direction = if percent.is_sign_negative() {
+            "down"
+        } else {
+            "up"
+        };
+        description.push_str(&format!(
+            ", {} {:.1}%",
+            direction,
+            rounded(percent.abs(), 1)
+        ));
+    }
+    if !realized.is_empty() {
+        description.push_str(&format!(", {}", realized));
+    }
+    description
+}
+
+// A dollar amount with its sign, e.g. "+$120.00" or "-$45.50"
+fn signed_dollars(amount: Decimal) -> String {
+    let sign = if amount.is_sign_negative() { "-" } else { "+" };
+    format!("{}${:.2}", sign, rounded(amount.abs(), 2))
+}
+
 fn play_alert_sound() {
     play_sound_internal("/System/Library/Sounds/Ping.aiff");
 }
@@ -819,6 +2631,11 @@ fn play_anomaly_sound() {
     play_sound_internal("/System/Library/Sounds/Funk.aiff");
 }
 
+fn play_watched_wallet_sound() {
+    // Tell trades from followed wallets apart from whales
+    play_sound_internal("/System/Library/Sounds/Glass.aiff");
+}
+
 fn play_sound_internal(_sound_file: &str) {
     // macOS: Use afplay with system sound
     #[cfg(target_os = "macos")]
@@ -858,96 +2675,135 @@ fn play_sound_internal(_sound_file: &str) {
     io::stdout().flush().ok();
 }
 
+// Signs that a trade is more than just a big bet. Time-based checks are
+// made as of the trade, so backfilled trades are judged like live ones.
 fn detect_anomalies(
-    price: f64,
-    size: f64,
-    value: f64,
+    trade: &types::NormalizedTrade,
     wallet_activity: Option<&types::WalletActivity>,
-) {
+) -> Vec<String> {
+    let (price, size, value) = (trade.price, trade.contracts, trade.notional);
     let mut anomalies = Vec::new();
 
     // Wallet-based anomalies (highest priority)
     if let Some(activity) = wallet_activity {
-        if activity.is_heavy_actor {
-            anomalies.push(format!(
-                "HEAVY ACTOR: {} transactions worth ${:.2} in last 24h",
-                activity.transactions_last_day, activity.total_value_day
-            ));
-        }
-        if activity.is_repeat_actor && !activity.is_heavy_actor {
-            anomalies.push(format!(
-                "Repeat actor: {} transactions in last hour",
-                activity.transactions_last_hour
-            ));
+        for window in activity.windows.iter().filter(|w| w.reached_transactions()) {
+            match window.window.level {
+                actors::ActorLevel::Heavy => anomalies.push(format!(
+                    "HEAVY ACTOR: {} transactions worth ${:.2} in last {}",
+                    window.transactions,
+                    rounded(window.volume, 2),
+                    window.window.window
+                )),
+                // A heavy actor is already called out
+                actors::ActorLevel::Repeat if !activity.is_heavy_actor => anomalies.push(format!(
+                    "Repeat actor: {} transactions in last {}",
+                    window.transactions, window.window.window
+                )),
+                actors::ActorLevel::Repeat => {}
+            }
         }
-        if activity.total_value_hour > 200000.0 {
+        for window in activity.windows.iter().filter(|w| w.reached_volume()) {
             anomalies.push(format!(
-                "Coordinated activity: ${:.0} volume in past hour",
-                activity.total_value_hour
+                "Coordinated activity: ${:.0} volume in past {}",
+                rounded(window.volume, 0),
+                window.window.window
             ));
         }
     }
 
+    // Splits, merges and redeems change hands at par, so their price and
+    // size say nothing about the market
+    if trade.kind != types::TradeKind::Trade {
+        return anomalies;
+    }
+
     // Extreme confidence (very high or very low probability)
-    if price > 0.95 {
+    if price > dec!(0.95) {
         anomalies.push(format!(
             "Extreme confidence bet ({:.1}% probability)",
-            price * 100.0
+            rounded(price * dec!(100), 1)
         ));
-    } else if price < 0.05 {
+    } else if price < dec!(0.05) {
         anomalies.push(format!(
             "Contrarian position ({:.1}% probability)",
-            price * 100.0
+            rounded(price * dec!(100), 1)
         ));
     }
 
     // Unusual size relative to typical market activity
-    if size > 100000.0 {
+    if size > dec!(100000) {
         anomalies.push("Exceptionally large position size".to_string());
     }
 
     // Very large single transaction
-    if value > 100000.0 {
-        anomalies.push(format!("Major capital deployment: ${:.0}", value));
+    if value > dec!(100000) {
+        anomalies.push(format!(
+            "Major capital deployment: ${:.0}",
+            rounded(value, 0)
+        ));
     }
 
     // Edge case: betting on near-certain outcomes with large size
-    if price > 0.90 && size > 50000.0 {
+    if price > dec!(0.90) && size > dec!(50000) {
         anomalies.push("High conviction in likely outcome".to_string());
     }
 
     // Edge case: large bet on unlikely outcome (potential insider info or hedge)
-    if price < 0.20 && value > 50000.0 {
+    if price < dec!(0.20) && value > dec!(50000) {
         anomalies.push(
             "Significant bet on unlikely outcome - possible hedge or information asymmetry"
                 .to_string(),
         );
     }
 
-    // Display anomalies
-    if !anomalies.is_empty() {
-        // Play distinctive anomaly sound
-        play_anomaly_sound();
-
-        println!();
-        println!("{}", "[ANOMALY INDICATORS]".bright_red().bold());
-        for anomaly in anomalies {
-            println!("  - {}", anomaly.yellow());
+    // Market-based anomalies, where details are known
+    if let Some(ref market) = trade.market {
+        if let Some(end) = market.end_time() {
+            let hours_left = (end - trade.timestamp).num_hours();
+            if (0..24).contains(&hours_left) {
+                anomalies.push(format!(
+                    "Late positioning: market closes {}",
+                    format_time_until(end, trade.timestamp)
+                ));
+            }
+        }
+        let liquidity = market.liquidity.and_then(|l| Decimal::try_from(l).ok());
+        if let Some(liquidity) = liquidity.filter(|l| *l > Decimal::ZERO) {
+            if value > liquidity * dec!(0.1) {
+                anomalies.push(format!(
+                    "Large relative to market: {:.0}% of ${:.0} liquidity",
+                    rounded(value / liquidity * dec!(100), 0),
+                    rounded(liquidity, 0)
+                ));
+            }
         }
     }
+
+    anomalies
 }
 
 struct WebhookAlert<'a> {
-    platform: &'a str,
+    platform: types::Platform,
     market_title: Option<&'a str>,
     outcome: Option<&'a str>,
-    side: &'a str,
-    value: f64,
-    price: f64,
-    size: f64,
-    timestamp: &'a str,
+    side: types::Side,
+    kind: types::TradeKind,
+    value: Decimal,
+    cost: Option<Decimal>,
+    max_payout: Option<Decimal>,
+    price: Decimal,
+    size: Decimal,
+    timestamp: String,
     wallet_id: Option<&'a str>,
     wallet_activity: Option<&'a types::WalletActivity>,
+    market_details: Option<&'a types::MarketInfo>,
+    exposure: Option<&'a types::Exposure>,
+    position: Option<types::PositionChange>,
+    holding: Option<&'a positions::Holding>,
+    watched_wallet: Option<&'a watchlist::WatchedWallet>,
+    /// The trader cut back a position they built earlier
+    exit: bool,
+    backfilled: bool,
 }
 
 // Sanitize text for messaging platforms that use Markdown/HTML parsing
@@ -970,50 +2826,69 @@ fn escape_special_chars(s: &str) -> String {
         .join(" ")
 }
 
-async fn send_webhook_alert(webhook_url: &str, alert: WebhookAlert<'_>) {
+async fn send_webhook_alert(http: &http::HttpClient, webhook_url: &str, alert: WebhookAlert<'_>) {
     use serde_json::json;
 
-    let is_sell = alert.side.to_uppercase() == "SELL";
-    let alert_type = if is_sell { "WHALE_EXIT" } else { "WHALE_ENTRY" };
+    let alert_type = if alert.exit {
+        "WHALE_EXIT"
+    } else {
+        "WHALE_ENTRY"
+    };
 
     let mut payload = json!({
-        "platform": alert.platform,
+        "platform": alert.platform.to_string(),
         "alert_type": alert_type,
-        "action": alert.side.to_uppercase(),
+        "action": alert.side.to_string(),
         "value": alert.value,
+        "cost": alert.cost,
+        "max_payout": alert.max_payout,
         "price": alert.price,
-        "price_percent": (alert.price * 100.0).round() as i32,
+        "price_percent": price_percent(alert.price),
         "size": alert.size,
         "timestamp": alert.timestamp,
         "market_title": alert.market_title.map(escape_special_chars),
         "outcome": alert.outcome.map(escape_special_chars),
     });
 
+    if alert.kind != types::TradeKind::Trade {
+        payload["activity"] = json!(alert.kind);
+    }
+
     // Add wallet information if available
     if let Some(wallet) = alert.wallet_id {
         payload["wallet_id"] = json!(wallet);
     }
+    if let Some(watched) = alert.watched_wallet {
+        payload["watched_wallet"] = json!(watched);
+    }
 
     if let Some(activity) = alert.wallet_activity {
-        payload["wallet_activity"] = json!({
-            "transactions_last_hour": activity.transactions_last_hour,
-            "transactions_last_day": activity.transactions_last_day,
-            "total_value_hour": activity.total_value_hour,
-            "total_value_day": activity.total_value_day,
-            "is_repeat_actor": activity.is_repeat_actor,
-            "is_heavy_actor": activity.is_heavy_actor,
-        });
+        payload["wallet_activity"] = wallet_activity_json(activity);
+    }
+
+    if let Some(details) = alert.market_details {
+        add_market_details(&mut payload, details);
+    }
+
+    add_position(&mut payload, &alert);
+
+    if alert.backfilled {
+        payload["backfilled"] = json!(true);
     }
 
-    // Send POST request to webhook
-    // For self-hosted instances with self-signed certs, accept invalid certs
-    let client = reqwest::Client::builder()
-        .danger_accept_invalid_certs(true)
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap();
+    post_webhook(http, webhook_url, &payload).await;
+}
+
+fn price_percent(price: Decimal) -> i32 {
+    (price * dec!(100)).round().to_i32().unwrap_or_default()
+}
 
-    match client.post(webhook_url).json(&payload).send().await {
+async fn post_webhook(http: &http::HttpClient, webhook_url: &str, payload: &serde_json::Value) {
+    // Send POST request to webhook, never twice
+    match http
+        .deliver(|client| client.post(webhook_url).json(payload))
+        .await
+    {
         Ok(response) => {
             if !response.status().is_success() {
                 eprintln!(
@@ -1029,49 +2904,148 @@ async fn send_webhook_alert(webhook_url: &str, alert: WebhookAlert<'_>) {
     }
 }
 
+// Market details shared by the webhook payload and the history log
+fn add_market_details(entry: &mut serde_json::Value, details: &types::MarketInfo) {
+    use serde_json::json;
+
+    entry["market_end_date"] = json!(details.end_date);
+    entry["market_category"] = json!(details.category);
+    entry["market_tags"] = json!(details.tags);
+    entry["market_liquidity"] = json!(details.liquidity);
+    entry["market_volume_24h"] = json!(details.volume_24h);
+    entry["market_event"] = json!(details.event);
+    entry["market_url"] = json!(details.url);
+}
+
+// Exposure and position shared by the webhook payload and the history log
+fn add_position(entry: &mut serde_json::Value, alert: &WebhookAlert) {
+    use serde_json::json;
+
+    if let Some(exposure) = alert.exposure {
+        entry["exposure"] = json!(exposure);
+    }
+    if let Some(position) = alert.position {
+        entry["position"] = json!(position);
+    }
+    if let Some(holding) = alert.holding {
+        entry["holding"] = holding_json(holding);
+    }
+}
+
+// A holding with its average price, value and PnL worked out
+fn holding_json(holding: &positions::Holding) -> serde_json::Value {
+    use serde_json::json;
+
+    json!({
+        "market_id": holding.market_id,
+        "outcome_id": holding.outcome_id,
+        "outcome": holding.outcome,
+        "contracts": holding.contracts,
+        "avg_price": holding.avg_price().round_dp(6),
+        "cost_basis": holding.cost_basis.round_dp(6),
+        "mark_price": holding.mark_price,
+        "market_value": holding.market_value().round_dp(6),
+        "unrealized_pnl": holding.unrealized_pnl().round_dp(6),
+        "realized_pnl": holding.realized_pnl.round_dp(6),
+        "return_percent": holding.return_percent().map(|p| p.round_dp(2)),
+    })
+}
+
+// Wallet activity as the webhook payload and the history log carry it
+fn wallet_activity_json(activity: &types::WalletActivity) -> serde_json::Value {
+    use serde_json::json;
+
+    let windows: Vec<serde_json::Value> = activity
+        .windows
+        .iter()
+        .map(|w| {
+            json!({
+                "window": w.window.window.to_string(),
+                "seconds": w.window.window.secs(),
+                "transactions": w.transactions,
+                "volume": w.volume,
+                "level": w.window.level.to_string(),
+                "reached_transactions": w.reached_transactions(),
+                "reached_volume": w.reached_volume(),
+            })
+        })
+        .collect();
+
+    json!({
+        "transactions_last_hour": activity.transactions_last_hour,
+        "transactions_last_day": activity.transactions_last_day,
+        "total_value_hour": activity.total_value_hour,
+        "total_value_day": activity.total_value_day,
+        "is_repeat_actor": activity.is_repeat_actor,
+        "is_heavy_actor": activity.is_heavy_actor,
+        "windows": windows,
+    })
+}
+
 fn get_history_file_path() -> Result<std::path::PathBuf, Box<dyn std::error::Error>> {
-    let config_dir = dirs::config_dir()
-        .ok_or("Could not determine config directory")?;
-    let wwatcher_dir = config_dir.join("wwatcher");
-    std::fs::create_dir_all(&wwatcher_dir)?;
-    Ok(wwatcher_dir.join("alert_history.jsonl"))
+    config::data_path("alert_history.jsonl")
+}
+
+// Every logged alert, oldest first
+fn load_alert_history(
+    history_file: &std::path::Path,
+) -> Result<Vec<serde_json::Value>, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(history_file)?;
+    Ok(contents
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
 }
 
 fn log_alert(alert: &WebhookAlert) {
     use serde_json::json;
-    
+
     if let Ok(history_file) = get_history_file_path() {
-        let is_sell = alert.side.to_uppercase() == "SELL";
-        let alert_type = if is_sell { "WHALE_EXIT" } else { "WHALE_ENTRY" };
-        
+        let alert_type = if alert.exit {
+            "WHALE_EXIT"
+        } else {
+            "WHALE_ENTRY"
+        };
+
         let mut log_entry = json!({
-            "platform": alert.platform,
+            "platform": alert.platform.to_string(),
             "alert_type": alert_type,
-            "action": alert.side.to_uppercase(),
+            "action": alert.side.to_string(),
             "value": alert.value,
+            "cost": alert.cost,
+            "max_payout": alert.max_payout,
             "price": alert.price,
-            "price_percent": (alert.price * 100.0).round() as i32,
+            "price_percent": price_percent(alert.price),
             "size": alert.size,
             "timestamp": alert.timestamp,
             "market_title": alert.market_title,
             "outcome": alert.outcome,
         });
-        
+
+        if alert.kind != types::TradeKind::Trade {
+            log_entry["activity"] = json!(alert.kind);
+        }
         if let Some(wallet) = alert.wallet_id {
             log_entry["wallet_id"] = json!(wallet);
         }
-        
+        if let Some(watched) = alert.watched_wallet {
+            log_entry["watched_wallet"] = json!(watched);
+        }
+
         if let Some(activity) = alert.wallet_activity {
-            log_entry["wallet_activity"] = json!({
-                "transactions_last_hour": activity.transactions_last_hour,
-                "transactions_last_day": activity.transactions_last_day,
-                "total_value_hour": activity.total_value_hour,
-                "total_value_day": activity.total_value_day,
-                "is_repeat_actor": activity.is_repeat_actor,
-                "is_heavy_actor": activity.is_heavy_actor,
-            });
+            log_entry["wallet_activity"] = wallet_activity_json(activity);
+        }
+
+        if let Some(details) = alert.market_details {
+            add_market_details(&mut log_entry, details);
+        }
+
+        add_position(&mut log_entry, alert);
+
+        if alert.backfilled {
+            log_entry["backfilled"] = json!(true);
         }
-        
+
         if let Ok(mut file) = std::fs::OpenOptions::new()
             .create(true)
             .append(true)
@@ -1084,45 +3058,445 @@ fn log_alert(alert: &WebhookAlert) {
     }
 }
 
-fn show_alert_history(limit: usize, platform_filter: &str, as_json: bool) -> Result<(), Box<dyn std::error::Error>> {
+fn manage_wallets(command: WalletsCommand) -> Result<(), Box<dyn std::error::Error>> {
+    let mut config = config::load_config()?;
+
+    match command {
+        WalletsCommand::Add {
+            address,
+            label,
+            tags,
+            threshold,
+        } => {
+            let existing = config.wallets.iter().position(|w| w.matches(&address));
+            let wallet = match existing {
+                Some(index) => &mut config.wallets[index],
+                None => {
+                    config.wallets.push(watchlist::WatchedWallet {
+                        address,
+                        ..Default::default()
+                    });
+                    config.wallets.last_mut().expect("just pushed")
+                }
+            };
+            // Only the settings given are changed
+            if label.is_some() {
+                wallet.label = label;
+            }
+            if !tags.is_empty() {
+                wallet.tags = tags;
+            }
+            if threshold.is_some() {
+                wallet.threshold = threshold;
+            }
+
+            let verb = if existing.is_some() {
+                "Updated"
+            } else {
+                "Watching"
+            };
+            println!("{} {}", verb, wallet.name().bright_green());
+            config::save_config(&config)?;
+        }
+        WalletsCommand::Remove { address } => {
+            let before = config.wallets.len();
+            config.wallets.retain(|w| !w.matches(&address));
+            if config.wallets.len() == before {
+                return Err(format!("{} is not a watched wallet", address).into());
+            }
+            config::save_config(&config)?;
+            println!("Stopped watching {}", address);
+        }
+        WalletsCommand::List { json } => {
+            if json {
+                println!("{}", serde_json::to_string_pretty(&config.wallets)?);
+            } else if config.wallets.is_empty() {
+                println!("No watched wallets.");
+                println!(
+                    "Add one with {}",
+                    "wwatcher wallets add <ADDRESS> --label <NAME>".bright_cyan()
+                );
+            } else {
+                println!("{}", "WATCHED WALLETS".bright_cyan().bold());
+                for wallet in &config.wallets {
+                    println!();
+                    println!("{}", wallet.name().bright_yellow());
+                    if wallet.label.is_some() {
+                        println!("Address:   {}", wallet.address);
+                    }
+                    if !wallet.tags.is_empty() {
+                        println!("Tags:      {}", wallet.tags.join(", "));
+                    }
+                    match wallet.threshold {
+                        Some(threshold) => {
+                            println!("Threshold: ${}", format_number(threshold))
+                        }
+                        None => println!("Threshold: every trade"),
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// A wallet address as typed, in the form the platform's trades carry it.
+// Polymarket reports addresses in lowercase.
+fn actor_id(address: &str, platform: types::Platform) -> String {
+    match platform {
+        types::Platform::Polymarket => address.to_lowercase(),
+        _ => address.to_string(),
+    }
+}
+
+fn show_positions(
+    address: &str,
+    platform: types::Platform,
+    as_json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let wallet = actor_id(address, platform);
+    let path = config::data_path(&format!(
+        "wallets_{}.db",
+        platform.to_string().to_lowercase()
+    ))?;
+    if !path.exists() {
+        println!("No {} wallet data found.", platform);
+        println!(
+            "Run {} to start booking holdings.",
+            "wwatcher watch --persist-wallets".bright_cyan()
+        );
+        return Ok(());
+    }
+
+    // Only read here, so the retention doesn't matter
+    let store = wallet_store::WalletStore::open(&path, 86400)?;
+    let holdings = store.load_book(Some(&wallet))?.holdings(&wallet);
+
+    if as_json {
+        let holdings: Vec<serde_json::Value> = holdings.iter().map(holding_json).collect();
+        println!("{}", serde_json::to_string_pretty(&holdings)?);
+        return Ok(());
+    }
+
+    if holdings.is_empty() {
+        println!("No holdings booked for {} on {}.", wallet, platform);
+        return Ok(());
+    }
+
+    let open: Vec<&positions::Holding> = holdings.iter().filter(|h| h.is_open()).collect();
+    let value: Decimal = open.iter().map(|h| h.market_value()).sum();
+    let unrealized: Decimal = open.iter().map(|h| h.unrealized_pnl()).sum();
+    let realized: Decimal = holdings.iter().map(|h| h.realized_pnl).sum();
+
+    println!("{}", "POSITIONS".bright_cyan().bold());
+    println!("Wallet:     {} ({})", wallet, platform);
+    println!(
+        "Open:       {} holdings worth ${:.2}",
+        open.len(),
+        rounded(value, 2)
+    );
+    println!("Unrealized: {}", signed_dollars(unrealized));
+    println!("Realized:   {}", signed_dollars(realized));
+    println!();
+
+    for (i, holding) in holdings.iter().enumerate() {
+        let title = holding
+            .market_title
+            .as_deref()
+            .unwrap_or(&holding.market_id);
+        println!("{}", format!("#{} | {}", i + 1, title).bright_yellow());
+        println!("Holding: {}", describe_holding(holding));
+        if holding.is_open() {
+            println!(
+                "Size:    {:.2} contracts, cost ${:.2}, marked at {:.4}",
+                rounded(holding.contracts, 2),
+                rounded(holding.cost_basis, 2),
+                rounded(holding.mark_price, 4)
+            );
+        }
+        let updated = chrono::DateTime::from_timestamp(holding.updated, 0).unwrap_or_default();
+        println!("Traded:  {}", updated.to_rfc3339().dimmed());
+        println!();
+    }
+
+    Ok(())
+}
+
+// Markets listed under a wallet's top markets
+const TOP_MARKETS: usize = 5;
+
+/// Everything known about one wallet: what the watcher tracked and alerted
+/// on, and on Polymarket its positions and recent activity.
+async fn show_wallet_profile(
+    address: &str,
+    platform: types::Platform,
+    max_pages: usize,
+    as_json: bool,
+    overrides: &config::EndpointOverrides,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use serde_json::json;
+
+    let cfg = config::load_config().unwrap_or_default();
+    let wallet = actor_id(address, platform);
+    let watched = cfg.wallets.iter().find(|w| w.matches(&wallet)).cloned();
+
+    // What `watch --persist-wallets` recorded
+    let path = config::data_path(&format!(
+        "wallets_{}.db",
+        platform.to_string().to_lowercase()
+    ))?;
+    let mut activity = None;
+    let mut first_seen = None;
+    if path.exists() {
+        let windows = actors::pick(Vec::new(), &cfg.actor_windows);
+        let tracker = types::WalletTracker::persistent(&path, windows)?;
+        activity = Some(tracker.get_activity(&wallet, chrono::Utc::now().timestamp()));
+        // Only read here, so the retention doesn't matter
+        first_seen = wallet_store::WalletStore::open(&path, 86400)?
+            .first_seen(&wallet)?
+            .and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+    }
+
+    // The wallet's alerts, newest first
+    let history_file = get_history_file_path()?;
+    let mut alerts = if history_file.exists() {
+        load_alert_history(&history_file)?
+    } else {
+        Vec::new()
+    };
+    alerts.retain(|alert| {
+        let field = |key| alert.get(key).and_then(|v| v.as_str());
+        field("wallet_id").is_some_and(|w| w.eq_ignore_ascii_case(&wallet))
+            && field("platform") == Some(platform.to_string().as_str())
+    });
+    alerts.reverse();
+
+    // Alerts are kept longer than wallet activity
+    let first_alert = alerts
+        .iter()
+        .filter_map(|a| a.get("timestamp").and_then(|v| v.as_str()))
+        .filter_map(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+        .map(|ts| ts.with_timezone(&chrono::Utc))
+        .min();
+    let first_seen = first_seen.into_iter().chain(first_alert).min();
+
+    let mut positions = Vec::new();
+    let mut portfolio_value = None;
+    let mut recent = Vec::new();
+    if platform == types::Platform::Polymarket {
+        let endpoints = config::Endpoints::resolve(&cfg, overrides);
+        let api_url = endpoints.polymarket_data_api.as_str();
+        let http = http::HttpClient::new(http::HttpSettings::from_config(&cfg));
+
+        match polymarket::fetch_wallet_positions(&http, api_url, &wallet).await {
+            Ok(found) => positions = found,
+            Err(e) => eprintln!("{} Positions lookup failed: {}", "[WARNING]".yellow(), e),
+        }
+        match polymarket::fetch_portfolio_value(&http, api_url, &wallet).await {
+            Ok(value) => portfolio_value = value,
+            Err(e) => eprintln!(
+                "{} Portfolio value lookup failed: {}",
+                "[WARNING]".yellow(),
+                e
+            ),
+        }
+        let feed = polymarket::TradeFeed::Wallet(&wallet);
+        match polymarket::fetch_trades_until(&http, api_url, feed, max_pages, |_| false).await {
+            Ok(batch) => recent = batch.map(polymarket::Trade::normalize).trades,
+            Err(e) => eprintln!("{} Activity lookup failed: {}", "[WARNING]".yellow(), e),
+        }
+    }
+
+    let record = profile::Record::from_activity(&recent, &positions);
+    let top_markets = profile::top_markets(&recent, TOP_MARKETS);
+    // Positions in resolved markets are only waiting to be redeemed
+    let open: Vec<&polymarket::WalletPosition> =
+        positions.iter().filter(|p| !p.redeemable).collect();
+
+    if as_json {
+        let profile = json!({
+            "wallet": wallet,
+            "platform": platform.to_string(),
+            "watched_wallet": watched,
+            "first_seen": first_seen.map(|ts| ts.to_rfc3339()),
+            "wallet_activity": activity.as_ref().map(wallet_activity_json),
+            "portfolio_value": portfolio_value,
+            "positions": open,
+            "record": {
+                "wins": record.wins,
+                "losses": record.losses,
+                "win_percent": record.win_percent().map(|p| p.round_dp(2)),
+            },
+            "recent_activity": recent.len(),
+            "top_markets": top_markets,
+            "alerts": alerts,
+        });
+        println!("{}", serde_json::to_string_pretty(&profile)?);
+        return Ok(());
+    }
+
+    println!("{}", "WALLET PROFILE".bright_cyan().bold());
+    println!("Wallet:     {} ({})", wallet, platform);
+    if let Some(ref watched) = watched {
+        let mut name = watched.name().to_string();
+        if !watched.tags.is_empty() {
+            name.push_str(&format!(" [{}]", watched.tags.join(", ")));
+        }
+        println!("Watched:    {}", name.bright_magenta().bold());
+    }
+    match first_seen {
+        Some(ts) => println!("First seen: {}", ts.format("%Y-%m-%d %H:%M UTC")),
+        None => println!("First seen: {}", "never".dimmed()),
+    }
+    if platform == types::Platform::Polymarket {
+        if let Some(value) = portfolio_value {
+            println!(
+                "Portfolio:  {} in {} open positions",
+                format!("${:.2}", rounded(value, 2)).bright_green(),
+                open.len()
+            );
+        }
+        let percent = record
+            .win_percent()
+            .map(|p| format!(" ({:.1}%)", rounded(p, 1)))
+            .unwrap_or_default();
+        println!(
+            "Record:     {} won, {} lost{} on resolved markets",
+            record.wins.to_string().bright_green(),
+            record.losses.to_string().bright_red(),
+            percent
+        );
+    }
+
+    println!();
+    println!("{}", "TRACKED ACTIVITY".dimmed());
+    match activity {
+        Some(ref activity) => print_window_activity(activity),
+        None => println!(
+            "Not tracked; run {} to keep wallet activity",
+            "wwatcher watch --persist-wallets".bright_cyan()
+        ),
+    }
+
+    if platform == types::Platform::Polymarket {
+        println!();
+        println!("{}", "OPEN POSITIONS".dimmed());
+        if open.is_empty() {
+            println!("  No open positions.");
+        }
+        for position in &open {
+            let title = position.title.as_deref().unwrap_or(&position.condition_id);
+            let outcome = position.outcome.as_deref().unwrap_or("?");
+            let mut line = format!(
+                "    '{}' {:.2} shares at avg {:.4}",
+                outcome,
+                rounded(position.size, 2),
+                rounded(position.avg_price.unwrap_or_default(), 4)
+            );
+            if let Some(price) = position.cur_price {
+                line.push_str(&format!(", now {:.4}", rounded(price, 4)));
+            }
+            if let Some(value) = position.current_value {
+                line.push_str(&format!(": ${:.2}", rounded(value, 2)));
+            }
+            if let Some(pnl) = position.cash_pnl {
+                line.push_str(&format!(", {}", signed_dollars(pnl)));
+            }
+            if let Some(percent) = position.percent_pnl {
+                line.push_str(&format!(" ({:+.1}%)", rounded(percent, 1)));
+            }
+            println!("  {}", title.bright_yellow());
+            println!("{}", line);
+        }
+
+        println!();
+        println!("{}", "TOP MARKETS".dimmed());
+        if top_markets.is_empty() {
+            println!("  No recent trades.");
+        }
+        for (i, market) in top_markets.iter().enumerate() {
+            let title = market.market_title.as_deref().unwrap_or(&market.market_id);
+            println!(
+                "  #{} {}: ${:.2} over {} trades",
+                i + 1,
+                title,
+                rounded(market.volume, 2),
+                market.trades
+            );
+        }
+    }
+
+    println!();
+    println!("{}", format!("ALERTS ({})", alerts.len()).dimmed());
+    if alerts.is_empty() {
+        println!("  No alerts in history.");
+    }
+    for alert in &alerts {
+        let field = |key| alert.get(key).and_then(|v| v.as_str());
+        let action = field("activity").or(field("action")).unwrap_or("UNKNOWN");
+        let value = decimal_field(alert, "value").unwrap_or_default();
+        let mut market = field("market_title")
+            .unwrap_or("Unknown market")
+            .to_string();
+        if let Some(outcome) = field("outcome") {
+            market.push_str(&format!(" '{}'", outcome));
+        }
+        println!(
+            "  {} | {} | ${:.2} | {}",
+            field("timestamp").unwrap_or("Unknown").dimmed(),
+            action,
+            rounded(value, 2),
+            market
+        );
+    }
+
+    Ok(())
+}
+
+fn show_alert_history(
+    limit: usize,
+    platform_filter: &str,
+    as_json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
     use serde_json::Value;
-    
+
     let history_file = get_history_file_path()?;
-    
+
     if !history_file.exists() {
         println!("No alert history found.");
-        println!("Run {} to start monitoring and logging alerts.", "wwatcher watch".bright_cyan());
+        println!(
+            "Run {} to start monitoring and logging alerts.",
+            "wwatcher watch".bright_cyan()
+        );
         return Ok(());
     }
-    
-    let contents = std::fs::read_to_string(&history_file)?;
-    let mut alerts: Vec<Value> = contents
-        .lines()
-        .filter_map(|line| serde_json::from_str(line).ok())
-        .collect();
-    
+
+    let mut alerts = load_alert_history(&history_file)?;
+
     // Filter by platform if specified
     if platform_filter != "all" {
         let filter_lower = platform_filter.to_lowercase();
         alerts.retain(|alert| {
-            alert.get("platform")
+            alert
+                .get("platform")
                 .and_then(|p| p.as_str())
                 .map(|p| p.to_lowercase() == filter_lower)
                 .unwrap_or(false)
         });
     }
-    
+
     // Reverse to show newest first
     alerts.reverse();
-    
+
     // Apply limit
     let alerts_to_show: Vec<&Value> = alerts.iter().take(limit).collect();
-    
+
     if alerts_to_show.is_empty() {
         println!("No alerts found matching filters.");
         return Ok(());
     }
-    
+
     if as_json {
         println!("{}", serde_json::to_string_pretty(&alerts_to_show)?);
     } else {
@@ -1132,43 +3506,129 @@ fn show_alert_history(limit: usize, platform_filter: &str, as_json: bool) -> Res
             println!("Platform filter: {}", platform_filter);
         }
         println!();
-        
+
         for (i, alert) in alerts_to_show.iter().enumerate() {
-            let platform = alert.get("platform").and_then(|v| v.as_str()).unwrap_or("Unknown");
-            let alert_type = alert.get("alert_type").and_then(|v| v.as_str()).unwrap_or("UNKNOWN");
-            let action = alert.get("action").and_then(|v| v.as_str()).unwrap_or("UNKNOWN");
-            let value = alert.get("value").and_then(|v| v.as_f64()).unwrap_or(0.0);
-            let timestamp = alert.get("timestamp").and_then(|v| v.as_str()).unwrap_or("Unknown");
-            let market_title = alert.get("market_title").and_then(|v| v.as_str()).unwrap_or("Unknown market");
+            let platform = alert
+                .get("platform")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown");
+            let alert_type = alert
+                .get("alert_type")
+                .and_then(|v| v.as_str())
+                .unwrap_or("UNKNOWN");
+            // Splits, merges and redeems name themselves rather than a side
+            let action = alert
+                .get("activity")
+                .or(alert.get("action"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("UNKNOWN");
+            let value = decimal_field(alert, "value").unwrap_or_default();
+            let timestamp = alert
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown");
+            let market_title = alert
+                .get("market_title")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Unknown market");
             let outcome = alert.get("outcome").and_then(|v| v.as_str());
-            
-            let header = format!("#{} | {} | {}", i + 1, platform, alert_type);
+
+            let mut header = format!("#{} | {} | {}", i + 1, platform, alert_type);
+            if alert.get("backfilled").and_then(|v| v.as_bool()) == Some(true) {
+                header.push_str(" | BACKFILLED");
+            }
             println!("{}", header.bright_yellow());
             println!("Time:   {}", timestamp.dimmed());
             println!("Market: {}", market_title);
             if let Some(out) = outcome {
                 println!("Outcome: {}", out);
             }
-            println!("Action: {} | Value: ${:.2}", action, value);
-            
+            print!("Action: {} | Value: ${:.2}", action, rounded(value, 2));
+            if let Some(payout) = decimal_field(alert, "max_payout") {
+                print!(" | Max payout: ${:.2}", rounded(payout, 2));
+            }
+            println!();
+            if let Some(url) = alert.get("market_url").and_then(|v| v.as_str()) {
+                println!("Link:   {}", url.dimmed());
+            }
+
+            if let Some(watched) = alert.get("watched_wallet") {
+                let label = watched
+                    .get("label")
+                    .or_else(|| watched.get("address"))
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("Unknown");
+                println!("Watched: {}", label.bright_magenta());
+            }
             if let Some(wallet_activity) = alert.get("wallet_activity") {
-                if let Some(txns_hour) = wallet_activity.get("transactions_last_hour").and_then(|v| v.as_u64()) {
+                if let Some(txns_hour) = wallet_activity
+                    .get("transactions_last_hour")
+                    .and_then(|v| v.as_u64())
+                {
                     if txns_hour > 1 {
                         println!("Wallet: {} txns in last hour", txns_hour);
                     }
                 }
             }
-            
+
             println!();
         }
-        
+
         println!("View as JSON: {} --json", "wwatcher history".bright_cyan());
-        println!("Filter by platform: {} --platform polymarket", "wwatcher history".bright_cyan());
+        println!(
+            "Filter by platform: {} --platform polymarket",
+            "wwatcher history".bright_cyan()
+        );
     }
-    
+
     Ok(())
 }
 
+// Decimals are truncated, not rounded, when formatted with a precision
+fn rounded(value: Decimal, places: u32) -> Decimal {
+    value.round_dp_with_strategy(places, RoundingStrategy::MidpointAwayFromZero)
+}
+
+// A money field of a history entry. Entries hold exact decimal numbers.
+fn decimal_field(entry: &serde_json::Value, key: &str) -> Option<Decimal> {
+    entry
+        .get(key)
+        .and_then(|v| serde::Deserialize::deserialize(v).ok())
+}
+
+// A --since/--until value: a date (midnight UTC) or an RFC 3339 time
+fn parse_time(s: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    if let Ok(time) = chrono::DateTime::parse_from_rfc3339(s) {
+        return Ok(time.with_timezone(&chrono::Utc));
+    }
+    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .map(|date| date.and_time(chrono::NaiveTime::MIN).and_utc())
+        .map_err(|_| format!("expected YYYY-MM-DD or an RFC 3339 time, got '{}'", s))
+}
+
+// Time left until `end` as seen from `from`
+fn format_time_until(
+    end: chrono::DateTime<chrono::Utc>,
+    from: chrono::DateTime<chrono::Utc>,
+) -> String {
+    let remaining = end - from;
+    if remaining.num_seconds() <= 0 {
+        return "closed".to_string();
+    }
+
+    let days = remaining.num_days();
+    let hours = remaining.num_hours() % 24;
+    if days > 0 {
+        format!("in {}d {}h", days, hours)
+    } else {
+        format!(
+            "in {}h {}m",
+            remaining.num_hours(),
+            remaining.num_minutes() % 60
+        )
+    }
+}
+
 fn format_number(n: u64) -> String {
     let s = n.to_string();
     let mut result = String::new();
@@ -1180,3 +3640,198 @@ fn format_number(n: u64) -> String {
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::sync::mpsc;
+
+    fn trade(id: &str, ts: i64, notional: Decimal) -> types::NormalizedTrade {
+        types::NormalizedTrade {
+            platform: types::Platform::Kalshi,
+            trade_id: id.to_string(),
+            market_id: "KXBTC-25-T1".to_string(),
+            market_title: None,
+            outcome_id: "yes".to_string(),
+            outcome: Some("YES".to_string()),
+            side: types::Side::Buy,
+            kind: types::TradeKind::Trade,
+            contracts: notional * dec!(2),
+            price: dec!(0.5),
+            notional,
+            cost: None,
+            max_payout: None,
+            exposure: None,
+            actor_id: None,
+            timestamp: chrono::DateTime::from_timestamp(ts, 0).unwrap(),
+            market: None,
+            raw: serde_json::Value::Null,
+        }
+    }
+
+    // Pages requested and the time stopped at, for each poll
+    type Polls = Arc<Mutex<Vec<(usize, Option<i64>)>>>;
+
+    // A source whose stream the test drives
+    struct FakeSource {
+        stream: Option<mpsc::Receiver<types::StreamEvent<types::NormalizedTrade>>>,
+        polls: Polls,
+        // Returned by the first poll that walks back to a stop
+        missed: Vec<types::NormalizedTrade>,
+        polls_while_streaming: bool,
+    }
+
+    impl source::TradeSource for FakeSource {
+        fn platform(&self) -> types::Platform {
+            types::Platform::Kalshi
+        }
+
+        async fn fetch_trades(
+            &mut self,
+            max_pages: usize,
+            stop_before: Option<i64>,
+        ) -> Result<types::TradeBatch<types::NormalizedTrade>, source::SourceError> {
+            self.polls.lock().unwrap().push((max_pages, stop_before));
+            let trades = if stop_before.is_some() {
+                std::mem::take(&mut self.missed)
+            } else {
+                Vec::new()
+            };
+            Ok(types::TradeBatch {
+                pages: 1,
+                received: trades.len(),
+                trades,
+                ..Default::default()
+            })
+        }
+
+        fn take_stream(
+            &mut self,
+        ) -> Option<mpsc::Receiver<types::StreamEvent<types::NormalizedTrade>>> {
+            self.stream.take()
+        }
+
+        fn polls_while_streaming(&self) -> bool {
+            self.polls_while_streaming
+        }
+    }
+
+    // Watch `source` with the given thresholds, polling every 20ms
+    fn watch(source: FakeSource, thresholds: watchlist::Thresholds) -> mpsc::Receiver<WatchEvent> {
+        let ctx = Arc::new(WatchContext::new(
+            None,
+            config::Endpoints::resolve(&config::Config::default(), &Default::default()),
+            None,
+            7,
+            None,
+        ));
+        let (alerts_tx, alerts_rx) = mpsc::channel(16);
+        tokio::spawn(watch_source(
+            ctx,
+            source,
+            Duration::from_millis(20),
+            seen::SeenTrades::new(3600, 1000),
+            types::WalletTracker::new(Vec::new()),
+            thresholds,
+            alerts_tx,
+        ));
+        alerts_rx
+    }
+
+    async fn next_alert(alerts: &mut mpsc::Receiver<WatchEvent>) -> Box<Alert> {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                if let Some(WatchEvent::Trade(alert)) = alerts.recv().await {
+                    return alert;
+                }
+            }
+        })
+        .await
+        .expect("no alert")
+    }
+
+    #[tokio::test]
+    async fn polls_back_over_a_stream_drop() {
+        let (stream_tx, stream_rx) = mpsc::channel(16);
+        stream_tx.send(types::StreamEvent::Connected).await.unwrap();
+        let live = trade("live", 1_700_000_000, dec!(10));
+        stream_tx
+            .send(types::StreamEvent::Trade(live))
+            .await
+            .unwrap();
+        stream_tx
+            .send(types::StreamEvent::Disconnected("reset".to_string()))
+            .await
+            .unwrap();
+
+        let polls = Polls::default();
+        let source = FakeSource {
+            stream: Some(stream_rx),
+            polls: polls.clone(),
+            missed: vec![trade("missed", 1_700_000_030, dec!(5000))],
+            polls_while_streaming: false,
+        };
+        let mut alerts = watch(source, watchlist::Thresholds::new(1000));
+
+        // The trade missed while disconnected is found and alerted on
+        let alert = next_alert(&mut alerts).await;
+        assert_eq!(alert.trade.trade_id, "missed");
+
+        // by walking back from the last streamed trade over every page
+        let polls = polls.lock().unwrap();
+        assert!(polls.contains(&(7, Some(1_700_000_000 - REORDER_WINDOW_SECS))));
+        drop(stream_tx);
+    }
+
+    #[tokio::test]
+    async fn polls_fill_in_wallets_while_streaming() {
+        let (stream_tx, stream_rx) = mpsc::channel(16);
+        stream_tx.send(types::StreamEvent::Connected).await.unwrap();
+        // Under the market threshold, so the stream doesn't alert on it
+        let streamed = trade("stream-id", 1_700_000_000, dec!(500));
+        stream_tx
+            .send(types::StreamEvent::Trade(streamed.clone()))
+            .await
+            .unwrap();
+
+        let polled = types::NormalizedTrade {
+            trade_id: "rest-id".to_string(),
+            actor_id: Some("0xwhale".to_string()),
+            ..streamed
+        };
+        let source = FakeSource {
+            stream: Some(stream_rx),
+            polls: Polls::default(),
+            missed: vec![polled],
+            polls_while_streaming: true,
+        };
+        let whale = watchlist::WatchedWallet {
+            address: "0xwhale".to_string(),
+            label: None,
+            tags: Vec::new(),
+            threshold: None,
+        };
+        let thresholds = watchlist::Thresholds::new(1000).with_wallets(&[whale]);
+        let mut alerts = watch(source, thresholds);
+
+        // The poll names the wallet, which is watched, so the trade alerts once
+        let alert = next_alert(&mut alerts).await;
+        assert_eq!(alert.trade.trade_id, "rest-id");
+        assert_eq!(
+            alert.watched_wallet.map(|w| w.address).as_deref(),
+            Some("0xwhale")
+        );
+        drop(stream_tx);
+    }
+
+    #[test]
+    fn polymarket_addresses_are_looked_up_in_lowercase() {
+        let address = "0xAbCdEf0123456789aBcDeF0123456789AbCdEf01";
+        assert_eq!(
+            actor_id(address, types::Platform::Polymarket),
+            "0xabcdef0123456789abcdef0123456789abcdef01"
+        );
+        assert_eq!(actor_id("JohnDoe", types::Platform::Kalshi), "JohnDoe");
+    }
+}
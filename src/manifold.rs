@@ -0,0 +1,479 @@
+use crate::http::HttpClient;
+use crate::market_cache::MarketCache;
+use crate::source::{self, SourceError, TradeSource};
+use crate::types::{
+    self, MarketInfo, NormalizedTrade, ParsedPage, Platform, Side, TradeBatch, TradeKind,
+};
+use colored::Colorize;
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ManifoldError {
+    #[error("HTTP request failed: {0}")]
+    RequestFailed(#[from] reqwest::Error),
+    #[error("Failed to parse response: {0}")]
+    ParseError(String),
+    #[error("Unexpected response format: {message} (payload: {sample})")]
+    SchemaError { message: String, sample: String },
+}
+
+/// Dollar value of one mana, used to put bets on the same scale as the
+/// real-money platforms. Mana is sold at 100 for $1.
+pub const DEFAULT_MANA_USD_RATE: Decimal = dec!(0.01);
+
+/// A filled bet from the public bets feed.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Bet {
+    pub id: String,
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    #[serde(rename = "contractId")]
+    pub contract_id: String,
+    /// Mana spent; negative when selling shares
+    pub amount: Decimal,
+    pub shares: Decimal,
+    /// YES or NO; on multiple choice markets, of the answer in `answer_id`
+    pub outcome: String,
+    #[serde(rename = "answerId", default)]
+    pub answer_id: Option<String>,
+    #[serde(rename = "probAfter", default)]
+    pub prob_after: Option<f64>,
+    /// Milliseconds since the epoch
+    #[serde(rename = "createdTime")]
+    pub created_time: i64,
+    #[serde(rename = "isRedemption", default)]
+    pub is_redemption: bool,
+    /// The item as the API returned it
+    #[serde(skip)]
+    pub raw: serde_json::Value,
+}
+
+impl Bet {
+    pub fn timestamp_secs(&self) -> i64 {
+        self.created_time / 1000
+    }
+
+    /// Convert to a trade, valuing mana at `mana_usd_rate` dollars.
+    pub fn normalize(self, mana_usd_rate: Decimal) -> NormalizedTrade {
+        let mana = self.amount.abs();
+        let contracts = self.shares.abs();
+        let side = if self.amount.is_sign_negative() {
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+
+        NormalizedTrade {
+            platform: Platform::Manifold,
+            trade_id: self.id,
+            market_id: self.contract_id,
+            market_title: None,
+            outcome_id: match self.answer_id {
+                Some(answer) => format!("{}:{}", answer, self.outcome),
+                None => self.outcome.clone(),
+            },
+            outcome: Some(self.outcome),
+            side,
+            kind: TradeKind::Trade,
+            contracts,
+            // Average price paid per share, in the market's probability terms
+            price: mana.checked_div(contracts).unwrap_or_default(),
+            notional: mana * mana_usd_rate,
+            cost: None,
+            // Each share pays 1 mana if its outcome wins
+            max_payout: (side == Side::Buy).then_some(contracts * mana_usd_rate),
+            exposure: None,
+            actor_id: Some(self.user_id),
+            timestamp: chrono::DateTime::from_timestamp_millis(self.created_time)
+                .unwrap_or_default(),
+            market: None,
+            raw: self.raw,
+        }
+    }
+}
+
+const PAGE_SIZE: usize = 100;
+
+/// Page backwards through the bets feed until `stop` matches a bet.
+///
+/// Returns the bets newer than the first match, newest first. If `stop`
+/// never matches within `max_pages`, the batch is flagged as a coverage gap.
+pub async fn fetch_bets_until<F>(
+    http: &HttpClient,
+    api_url: &str,
+    max_pages: usize,
+    stop: F,
+) -> Result<TradeBatch<Bet>, ManifoldError>
+where
+    F: Fn(&Bet) -> bool,
+{
+    let mut batch = TradeBatch::default();
+    let mut before: Option<String> = None;
+
+    for _ in 0..max_pages.max(1) {
+        let (page, last_id) = match fetch_bets_page(http, api_url, before.as_deref()).await {
+            Ok(page) => page,
+            // Keep the newer pages we already have; the rest is a gap
+            Err(_) if batch.pages > 0 => {
+                batch.coverage_gap = true;
+                return Ok(batch);
+            }
+            Err(e) => return Err(e),
+        };
+        batch.add_page(&page);
+
+        for bet in page.items {
+            if stop(&bet) {
+                return Ok(batch);
+            }
+            batch.trades.push(bet);
+        }
+
+        before = last_id;
+        if before.is_none() {
+            return Ok(batch);
+        }
+    }
+
+    batch.coverage_gap = true;
+    Ok(batch)
+}
+
+// One page of bets, plus the ID to continue from when the page was full
+async fn fetch_bets_page(
+    http: &HttpClient,
+    api_url: &str,
+    before: Option<&str>,
+) -> Result<(ParsedPage<Bet>, Option<String>), ManifoldError> {
+    let url = format!("{}/bets", api_url);
+    let mut query = vec![("limit", PAGE_SIZE.to_string())];
+    if let Some(before) = before {
+        query.push(("before", before.to_string()));
+    }
+
+    let response = http
+        .send(|client| {
+            client
+                .get(&url)
+                .query(&query)
+                .header("Accept", "application/json")
+        })
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(ManifoldError::ParseError(format!(
+            "API returned status: {}",
+            response.status()
+        )));
+    }
+
+    let text = response.text().await?;
+    parse_bets_page(&text)
+}
+
+fn parse_bets_page(text: &str) -> Result<(ParsedPage<Bet>, Option<String>), ManifoldError> {
+    let schema_error = |message: &str| ManifoldError::SchemaError {
+        message: message.to_string(),
+        sample: types::payload_sample(text),
+    };
+
+    let body: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| schema_error(&e.to_string()))?;
+    let serde_json::Value::Array(items) = body else {
+        return Err(schema_error("expected an array of bets"));
+    };
+
+    let last_id = if items.len() < PAGE_SIZE {
+        None
+    } else {
+        items
+            .last()
+            .and_then(|item| item.get("id"))
+            .and_then(|id| id.as_str())
+            .map(str::to_string)
+    };
+
+    // Redemptions and unfilled limit orders are bookkeeping, not trades
+    let items: Vec<serde_json::Value> = items
+        .into_iter()
+        .filter(|item| {
+            let redemption = item.get("isRedemption").and_then(|r| r.as_bool());
+            let amount = item.get("amount").and_then(|a| a.as_f64());
+            redemption != Some(true) && amount != Some(0.0)
+        })
+        .collect();
+
+    let page = types::parse_items(&items, |item| {
+        let mut bet = Bet::deserialize(item).ok()?;
+        bet.raw = item.clone();
+        Some(bet)
+    });
+
+    Ok((page, last_id))
+}
+
+/// Market details used to describe a bet.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Market {
+    pub id: String,
+    #[serde(default)]
+    pub question: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Milliseconds since the epoch
+    #[serde(rename = "closeTime", default)]
+    pub close_time: Option<i64>,
+    #[serde(rename = "groupSlugs", default)]
+    pub group_slugs: Vec<String>,
+    #[serde(rename = "totalLiquidity", default)]
+    pub total_liquidity: Option<f64>,
+    #[serde(rename = "volume24Hours", default)]
+    pub volume_24_hours: Option<f64>,
+    #[serde(default)]
+    pub answers: Vec<Answer>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Answer {
+    pub id: String,
+    pub text: String,
+}
+
+impl Market {
+    /// Liquidity and volume are in mana; they're converted like bets are.
+    pub fn info(&self, mana_usd_rate: Decimal) -> MarketInfo {
+        let rate = mana_usd_rate.to_f64().unwrap_or_default();
+        MarketInfo {
+            end_date: self
+                .close_time
+                .and_then(chrono::DateTime::from_timestamp_millis)
+                .map(|dt| dt.to_rfc3339()),
+            category: None,
+            tags: self.group_slugs.clone(),
+            liquidity: self.total_liquidity.map(|l| l * rate),
+            volume_24h: self.volume_24_hours.map(|v| v * rate),
+            event: None,
+            url: self.url.clone(),
+            outcomes: Vec::new(),
+        }
+    }
+
+    fn answer_text(&self, answer_id: &str) -> Option<&str> {
+        self.answers
+            .iter()
+            .find(|a| a.id == answer_id)
+            .map(|a| a.text.as_str())
+    }
+}
+
+pub async fn fetch_market(
+    http: &HttpClient,
+    api_url: &str,
+    market_id: &str,
+) -> Result<Market, ManifoldError> {
+    let url = format!("{}/market/{}", api_url, market_id);
+
+    let response = http
+        .send(|client| client.get(&url).header("Accept", "application/json"))
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(ManifoldError::ParseError(format!(
+            "API returned status: {}",
+            response.status()
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| ManifoldError::ParseError(e.to_string()))
+}
+
+/// Manifold adapter: the public bets feed, with mana converted to dollars.
+pub struct ManifoldSource {
+    http: HttpClient,
+    api_url: String,
+    mana_usd_rate: Decimal,
+    markets: MarketCache<Market>,
+}
+
+impl ManifoldSource {
+    pub fn new(
+        http: HttpClient,
+        api_url: String,
+        mana_usd_rate: Decimal,
+        markets: MarketCache<Market>,
+    ) -> Self {
+        Self {
+            http,
+            api_url,
+            mana_usd_rate,
+            markets,
+        }
+    }
+}
+
+impl TradeSource for ManifoldSource {
+    fn platform(&self) -> Platform {
+        Platform::Manifold
+    }
+
+    async fn fetch_trades(
+        &mut self,
+        max_pages: usize,
+        stop_before: Option<i64>,
+    ) -> Result<TradeBatch<NormalizedTrade>, SourceError> {
+        let batch = fetch_bets_until(&self.http, &self.api_url, max_pages, |b| {
+            source::is_older(Some(b.timestamp_secs()), stop_before)
+        })
+        .await?;
+
+        let rate = self.mana_usd_rate;
+        Ok(batch.map(|bet| bet.normalize(rate)))
+    }
+
+    async fn enrich(&mut self, trades: &mut [NormalizedTrade]) {
+        // There's no batch lookup, so markets are fetched one at a time
+        for market_id in self
+            .markets
+            .missing(trades.iter().map(|t| t.market_id.as_str()))
+        {
+            match fetch_market(&self.http, &self.api_url, &market_id).await {
+                Ok(market) => self.markets.insert(market_id, market),
+                Err(e) => {
+                    eprintln!(
+                        "{} Manifold market lookup failed: {}",
+                        "[WARNING]".yellow(),
+                        e
+                    );
+                }
+            }
+        }
+
+        for trade in trades {
+            let Some(market) = self.markets.get(&trade.market_id) else {
+                continue;
+            };
+            trade.market_title = market.question.clone();
+            trade.market = Some(market.info(self.mana_usd_rate));
+
+            // Name the answer on multiple choice markets, e.g. "Alice (YES)"
+            let answer_id = trade.outcome_id.split_once(':').map(|(id, _)| id);
+            if let Some(text) = answer_id.and_then(|id| market.answer_text(id)) {
+                trade.outcome = Some(format!(
+                    "{} ({})",
+                    text,
+                    trade.outcome.as_deref().unwrap_or("YES")
+                ));
+            }
+        }
+    }
+
+    fn save(&mut self) -> std::io::Result<()> {
+        self.markets.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BETS: &str = include_str!("../tests/fixtures/manifold/bets.json");
+    const MARKET: &str = include_str!("../tests/fixtures/manifold/market.json");
+
+    fn bets() -> Vec<Bet> {
+        let (page, _) = parse_bets_page(BETS).unwrap();
+        page.items
+    }
+
+    #[test]
+    fn redemptions_and_unfilled_orders_are_skipped() {
+        let (page, last_id) = parse_bets_page(BETS).unwrap();
+        let ids: Vec<&str> = page.items.iter().map(|b| b.id.as_str()).collect();
+        assert_eq!(ids, ["b1Yes", "b2Sell"]);
+        // The malformed bet is counted, not silently lost
+        assert_eq!(page.received, 3);
+        assert_eq!(page.dropped, 1);
+        // A short page is the last one
+        assert_eq!(last_id, None);
+    }
+
+    #[test]
+    fn bet_keeps_its_raw_item() {
+        let bet = &bets()[0];
+        assert_eq!(bet.raw["probBefore"], 0.6);
+        assert_eq!(bet.timestamp_secs(), 1_700_000_000);
+    }
+
+    #[test]
+    fn non_array_body_is_a_schema_error() {
+        let err = parse_bets_page(r#"{"error":"rate limited"}"#).unwrap_err();
+        assert!(matches!(err, ManifoldError::SchemaError { .. }));
+    }
+
+    #[test]
+    fn buy_is_valued_in_dollars() {
+        let trade = bets().remove(0).normalize(DEFAULT_MANA_USD_RATE);
+        assert_eq!(trade.platform, Platform::Manifold);
+        assert_eq!(trade.market_id, "c-binary");
+        assert_eq!(trade.outcome_id, "YES");
+        assert_eq!(trade.side, Side::Buy);
+        assert_eq!(trade.contracts, dec!(4000));
+        assert_eq!(trade.price, dec!(0.625));
+        assert_eq!(trade.notional, dec!(25));
+        assert_eq!(trade.max_payout, Some(dec!(40)));
+        assert_eq!(trade.actor_id.as_deref(), Some("u-alice"));
+        assert_eq!(trade.timestamp.timestamp_millis(), 1_700_000_000_123);
+    }
+
+    #[test]
+    fn sell_on_an_answer_is_keyed_by_answer() {
+        let trade = bets().remove(1).normalize(DEFAULT_MANA_USD_RATE);
+        assert_eq!(trade.outcome_id, "a-bob:NO");
+        assert_eq!(trade.outcome.as_deref(), Some("NO"));
+        assert_eq!(trade.side, Side::Sell);
+        assert_eq!(trade.contracts, dec!(500));
+        assert_eq!(trade.price, dec!(0.6));
+        assert_eq!(trade.notional, dec!(3));
+        assert_eq!(trade.max_payout, None);
+    }
+
+    #[test]
+    fn mana_rate_scales_dollar_amounts() {
+        let trade = bets().remove(0).normalize(dec!(0.001));
+        assert_eq!(trade.notional, dec!(2.5));
+        assert_eq!(trade.max_payout, Some(dec!(4)));
+        // Price is a probability, so it doesn't depend on the rate
+        assert_eq!(trade.price, dec!(0.625));
+    }
+
+    #[test]
+    fn market_info_is_converted_to_dollars() {
+        let market: Market = serde_json::from_str(MARKET).unwrap();
+        let info = market.info(DEFAULT_MANA_USD_RATE);
+        assert_eq!(info.liquidity, Some(120.0));
+        assert_eq!(info.volume_24h, Some(45.0));
+        assert_eq!(info.end_date.as_deref(), Some("2025-01-01T00:00:00+00:00"));
+        assert_eq!(info.tags, ["politics", "local"]);
+        assert_eq!(
+            info.url.as_deref(),
+            Some("https://manifold.markets/erin/who-will-win-the-club-election")
+        );
+    }
+
+    #[test]
+    fn answers_are_looked_up_by_id() {
+        let market: Market = serde_json::from_str(MARKET).unwrap();
+        assert_eq!(
+            market.question.as_deref(),
+            Some("Who will win the club election?")
+        );
+        assert_eq!(market.answer_text("a-bob"), Some("Bob"));
+        assert_eq!(market.answer_text("a-nobody"), None);
+    }
+}
@@ -0,0 +1,162 @@
+// Time-limited cache of market metadata, keyed by ticker
+//
+// Whale trades tend to arrive in bursts on the same market, so details are
+// fetched once and reused until they expire instead of refetched per trade.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_TTL_SECS: u64 = 3600;
+
+#[derive(Serialize, Deserialize)]
+struct Entry<T> {
+    fetched_at: u64,
+    market: T,
+}
+
+pub struct MarketCache<T> {
+    ttl_secs: u64,
+    entries: HashMap<String, Entry<T>>,
+    path: Option<PathBuf>,
+    dirty: bool,
+}
+
+impl<T: Serialize + DeserializeOwned> MarketCache<T> {
+    pub fn new(ttl_secs: u64) -> Self {
+        Self {
+            ttl_secs,
+            entries: HashMap::new(),
+            path: None,
+            dirty: false,
+        }
+    }
+
+    /// A cache that is loaded from and saved to `path`.
+    ///
+    /// A missing or unreadable file starts an empty cache.
+    pub fn persistent(path: PathBuf, ttl_secs: u64) -> Self {
+        let mut cache = Self::new(ttl_secs);
+
+        if let Ok(json) = std::fs::read_to_string(&path) {
+            if let Ok(entries) = serde_json::from_str::<HashMap<String, Entry<T>>>(&json) {
+                cache.entries = entries;
+                cache.evict(now());
+            }
+        }
+
+        cache.path = Some(path);
+        cache
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// The cached market, if it hasn't expired.
+    pub fn get(&self, key: &str) -> Option<&T> {
+        self.entries
+            .get(key)
+            .filter(|entry| now().saturating_sub(entry.fetched_at) < self.ttl_secs)
+            .map(|entry| &entry.market)
+    }
+
+    /// The keys that have no fresh entry, without duplicates.
+    pub fn missing<'a, I>(&self, keys: I) -> Vec<String>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let mut wanted = HashSet::new();
+        keys.into_iter()
+            .filter(|key| self.get(key).is_none() && wanted.insert(*key))
+            .map(str::to_string)
+            .collect()
+    }
+
+    pub fn insert(&mut self, key: String, market: T) {
+        let fetched_at = now();
+        self.entries.insert(key, Entry { fetched_at, market });
+        self.dirty = true;
+        self.evict(fetched_at);
+    }
+
+    /// Write the cache to disk if it is persistent and has changed.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(());
+        };
+        if !self.dirty {
+            return Ok(());
+        }
+
+        let json = serde_json::to_string(&self.entries)?;
+
+        // Write then rename so a crash mid-write doesn't lose the whole cache
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(tmp, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+
+    fn evict(&mut self, now: u64) {
+        let ttl_secs = self.ttl_secs;
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.fetched_at) < ttl_secs);
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("wwatcher-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn expired_entries_are_fetched_again() {
+        let path = temp_path("cache-expiry");
+        let entries = serde_json::json!({
+            "fresh": { "fetched_at": now() - 60, "market": "fresh market" },
+            "stale": { "fetched_at": now() - 7200, "market": "stale market" },
+        });
+        std::fs::write(&path, entries.to_string()).unwrap();
+
+        let cache = MarketCache::<String>::persistent(path.clone(), 3600);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get("fresh").map(String::as_str), Some("fresh market"));
+        assert_eq!(cache.get("stale"), None);
+        assert_eq!(
+            cache.missing(["stale", "fresh", "new", "stale"]),
+            ["stale", "new"]
+        );
+
+        let mut cache = MarketCache::<String>::new(0);
+        cache.insert("fresh".to_string(), "fresh market".to_string());
+        assert_eq!(cache.missing(["fresh"]), ["fresh"]);
+    }
+
+    #[test]
+    fn saved_entries_load_back() {
+        let path = temp_path("cache-save");
+        let mut cache = MarketCache::<String>::persistent(path.clone(), 3600);
+        cache.insert("KXBTCD".to_string(), "Bitcoin".to_string());
+        cache.save().unwrap();
+
+        let loaded = MarketCache::<String>::persistent(path.clone(), 3600);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.get("KXBTCD").map(String::as_str), Some("Bitcoin"));
+        assert!(loaded.missing(["KXBTCD"]).is_empty());
+    }
+}
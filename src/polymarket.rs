@@ -1,5 +1,15 @@
+use crate::http::HttpClient;
+use crate::market_cache::MarketCache;
+use crate::source::{self, SourceError, TradeSource};
+use crate::types::{
+    self, Exposure, MarketInfo, NormalizedTrade, ParsedPage, Platform, Side, StreamEvent,
+    TradeBatch, TradeKind,
+};
+use colored::Colorize;
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use tokio::sync::mpsc;
 
 #[derive(Error, Debug)]
 pub enum PolymarketError {
@@ -7,6 +17,8 @@ pub enum PolymarketError {
     RequestFailed(#[from] reqwest::Error),
     #[error("Failed to parse response: {0}")]
     ParseError(String),
+    #[error("Unexpected response format: {message} (payload: {sample})")]
+    SchemaError { message: String, sample: String },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,10 +31,13 @@ pub struct Trade {
     pub asset_id: String,
     #[serde(rename = "side")]
     pub side: String,
+    /// Split, merge or redeem, when taken from a wallet's activity
+    #[serde(skip)]
+    pub kind: TradeKind,
     #[serde(rename = "size")]
-    pub size: f64,
+    pub size: Decimal,
     #[serde(rename = "price")]
-    pub price: f64,
+    pub price: Decimal,
     #[serde(rename = "timestamp")]
     pub timestamp: String,
     #[serde(skip)]
@@ -31,12 +46,74 @@ pub struct Trade {
     pub outcome: Option<String>,
     #[serde(skip)]
     pub wallet_id: Option<String>,
+    /// Position of the outcome in its market (0 or 1)
+    #[serde(skip)]
+    pub outcome_index: Option<usize>,
+    /// The item as the API returned it
+    #[serde(skip)]
+    pub raw: serde_json::Value,
 }
 
-#[derive(Debug, Deserialize)]
-struct TradesResponse {
-    #[serde(default)]
-    data: Vec<ActivityItem>,
+impl Trade {
+    /// Identity of a single fill. One transaction can settle several fills,
+    /// so the transaction hash alone isn't unique.
+    pub fn fill_id(&self) -> String {
+        format!(
+            "{}:{}:{}:{}:{}:{}",
+            self.id,
+            self.asset_id,
+            self.side,
+            self.size.normalize(),
+            self.price.normalize(),
+            self.wallet_id.as_deref().unwrap_or("")
+        )
+    }
+
+    pub fn timestamp_secs(&self) -> Option<i64> {
+        chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|dt| dt.timestamp())
+    }
+
+    pub fn normalize(self) -> NormalizedTrade {
+        let side = if self.side.eq_ignore_ascii_case("SELL") {
+            Side::Sell
+        } else {
+            Side::Buy
+        };
+
+        let exposure = self.outcome_index.and_then(|index| {
+            let mut exposure = Exposure::binary(index, side, self.size, self.price)?;
+            if index == 0 {
+                exposure.outcome = self.outcome.clone();
+            }
+            Some(exposure)
+        });
+
+        NormalizedTrade {
+            platform: Platform::Polymarket,
+            trade_id: self.fill_id(),
+            timestamp: chrono::DateTime::parse_from_rfc3339(&self.timestamp)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .unwrap_or_default(),
+            market_id: self.market,
+            market_title: self.market_title,
+            outcome_id: self.asset_id,
+            outcome: self.outcome,
+            side,
+            kind: self.kind,
+            contracts: self.size,
+            price: self.price,
+            notional: self.size * self.price,
+            cost: None,
+            // Each share pays $1 if its outcome wins
+            max_payout: (side == Side::Buy && self.kind == TradeKind::Trade).then_some(self.size),
+            exposure,
+            actor_id: self.wallet_id,
+            market: None,
+            raw: self.raw,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,9 +127,9 @@ struct ActivityItem {
     #[serde(rename = "side")]
     side: Option<String>,
     #[serde(rename = "size")]
-    size: Option<f64>,
+    size: Option<Decimal>,
     #[serde(rename = "price")]
-    price: Option<f64>,
+    price: Option<Decimal>,
     #[serde(rename = "timestamp")]
     timestamp: Option<i64>,
     #[serde(rename = "name")]
@@ -66,20 +143,161 @@ struct ActivityItem {
     title: Option<String>,
     #[serde(rename = "outcome")]
     outcome: Option<String>,
+    #[serde(rename = "outcomeIndex")]
+    outcome_index: Option<usize>,
+    // Only on a wallet's activity
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(rename = "usdcSize")]
+    usdc_size: Option<Decimal>,
+}
+
+impl ActivityItem {
+    // Trades missing critical data are rejected
+    fn into_trade(self, raw: &serde_json::Value) -> Option<Trade> {
+        let kind = match self.kind.as_deref() {
+            // The trades feed doesn't say
+            None | Some("TRADE") => TradeKind::Trade,
+            Some("SPLIT") => TradeKind::Split,
+            Some("MERGE") => TradeKind::Merge,
+            Some("REDEEM") => TradeKind::Redeem,
+            Some(_) => return None,
+        };
+        let market = self.market?;
+        let size = self.size?;
+        let timestamp = chrono::DateTime::from_timestamp(self.timestamp?, 0)?;
+
+        let (asset_id, side, price, outcome_index) = match kind {
+            TradeKind::Trade => (self.asset?, self.side?, self.price?, self.outcome_index),
+            // These move a share of every outcome at once, at $1 a set (or
+            // whatever a resolved market pays out), so they have no side or
+            // price of their own. Splits take shares on; the others give
+            // them up.
+            _ => {
+                let side = match kind {
+                    TradeKind::Split => "BUY",
+                    _ => "SELL",
+                };
+                let asset_id = self
+                    .asset
+                    .filter(|a| !a.is_empty())
+                    .unwrap_or_else(|| market.clone());
+                let price = self.usdc_size?.checked_div(size)?;
+                (asset_id, side.to_string(), price, None)
+            }
+        };
+
+        Some(Trade {
+            id: self.id,
+            market,
+            asset_id,
+            side,
+            kind,
+            size,
+            price,
+            timestamp: timestamp.to_rfc3339(),
+            // New API includes title and outcome directly
+            market_title: self.title,
+            outcome: self.outcome.filter(|o| !o.is_empty()),
+            wallet_id: self.proxy_wallet.or(self.user).or(self.maker),
+            outcome_index,
+            raw: raw.clone(),
+        })
+    }
 }
 
-pub async fn fetch_recent_trades() -> Result<Vec<Trade>, PolymarketError> {
-    let client = reqwest::Client::new();
+const PAGE_SIZE: usize = 100;
+
+/// Activity types a followed wallet is alerted on. Rewards, rebates and the
+/// like aren't trades.
+const FOLLOWED_ACTIVITY: [&str; 4] = ["TRADE", "SPLIT", "MERGE", "REDEEM"];
+
+/// Which trades to page through.
+#[derive(Debug, Clone, Copy)]
+pub enum TradeFeed<'a> {
+    /// Everyone's trades, in these condition IDs (default: all markets)
+    Markets(&'a [String]),
+    /// One wallet's trades, splits, merges and redeems
+    Wallet(&'a str),
+}
+
+/// Page backwards through a trade feed until `stop` matches a trade.
+///
+/// Returns the trades newer than the first match, newest first. If `stop`
+/// never matches within `max_pages`, the batch is flagged as a coverage gap.
+pub async fn fetch_trades_until<F>(
+    http: &HttpClient,
+    api_url: &str,
+    feed: TradeFeed<'_>,
+    max_pages: usize,
+    stop: F,
+) -> Result<TradeBatch<Trade>, PolymarketError>
+where
+    F: Fn(&Trade) -> bool,
+{
+    let mut batch = TradeBatch::default();
+
+    for page in 0..max_pages.max(1) {
+        let (page, full) = match fetch_trades_page(http, api_url, feed, page * PAGE_SIZE).await {
+            Ok(page) => page,
+            // Keep the newer pages we already have; the rest is a gap
+            Err(_) if batch.pages > 0 => {
+                batch.coverage_gap = true;
+                return Ok(batch);
+            }
+            Err(e) => return Err(e),
+        };
+        batch.add_page(&page);
 
+        for trade in page.items {
+            if stop(&trade) {
+                return Ok(batch);
+            }
+            batch.trades.push(trade);
+        }
+
+        if !full {
+            return Ok(batch);
+        }
+    }
+
+    batch.coverage_gap = true;
+    Ok(batch)
+}
+
+// One page of trades, and whether it was full so there may be more
+async fn fetch_trades_page(
+    http: &HttpClient,
+    api_url: &str,
+    feed: TradeFeed<'_>,
+    offset: usize,
+) -> Result<(ParsedPage<Trade>, bool), PolymarketError> {
     // Use the Polymarket Data API to fetch recent activity
     // This is a public endpoint that doesn't require authentication
-    let url = "https://data-api.polymarket.com/trades";
+    let mut query = vec![
+        ("limit", PAGE_SIZE.to_string()),
+        ("offset", offset.to_string()),
+    ];
+    let url = match feed {
+        TradeFeed::Markets(markets) => {
+            if !markets.is_empty() {
+                query.push(("market", markets.join(",")));
+            }
+            format!("{}/trades", api_url)
+        }
+        TradeFeed::Wallet(wallet) => {
+            query.push(("user", wallet.to_string()));
+            format!("{}/activity", api_url)
+        }
+    };
 
-    let response = client
-        .get(url)
-        .query(&[("limit", "100")])
-        .header("Accept", "application/json")
-        .send()
+    let response = http
+        .send(|client| {
+            client
+                .get(&url)
+                .query(&query)
+                .header("Accept", "application/json")
+        })
         .await?;
 
     if !response.status().is_success() {
@@ -90,81 +308,672 @@ pub async fn fetch_recent_trades() -> Result<Vec<Trade>, PolymarketError> {
     }
 
     let text = response.text().await?;
+    let schema_error = |message: &str| PolymarketError::SchemaError {
+        message: message.to_string(),
+        sample: types::payload_sample(&text),
+    };
 
-    // Try to parse as array first (some endpoints return arrays directly)
-    if let Ok(items) = serde_json::from_str::<Vec<ActivityItem>>(&text) {
-        let trades = items
-            .into_iter()
-            .filter_map(|item| {
-                // Skip trades missing critical data
-                let market = item.market?;
-                let asset_id = item.asset?;
-                let side = item.side?;
-                let size = item.size?;
-                let price = item.price?;
-
-                Some(Trade {
-                    id: item.id.clone(),
-                    market,
-                    asset_id,
-                    side,
-                    size,
-                    price,
-                    timestamp: item
-                        .timestamp
-                        .and_then(|ts| {
-                            chrono::DateTime::from_timestamp(ts, 0)
-                                .map(|dt| dt.to_rfc3339())
-                        })
-                        .unwrap_or_else(|| format!("timestamp_error_{}", item.id)),
-                    // New API includes title and outcome directly
-                    market_title: item.title,
-                    outcome: item.outcome,
-                    wallet_id: item.proxy_wallet.or(item.user).or(item.maker),
-                })
-            })
-            .collect();
-        return Ok(trades);
-    }
-
-    // Try wrapped response format
-    if let Ok(wrapped) = serde_json::from_str::<TradesResponse>(&text) {
-        let trades = wrapped
-            .data
-            .into_iter()
-            .filter_map(|item| {
-                // Skip trades missing critical data
-                let market = item.market?;
-                let asset_id = item.asset?;
-                let side = item.side?;
-                let size = item.size?;
-                let price = item.price?;
-
-                Some(Trade {
-                    id: item.id.clone(),
-                    market,
-                    asset_id,
-                    side,
-                    size,
-                    price,
-                    timestamp: item
-                        .timestamp
-                        .and_then(|ts| {
-                            chrono::DateTime::from_timestamp(ts, 0)
-                                .map(|dt| dt.to_rfc3339())
-                        })
-                        .unwrap_or_else(|| format!("timestamp_error_{}", item.id)),
-                    // New API includes title and outcome directly
-                    market_title: item.title,
-                    outcome: item.outcome,
-                    wallet_id: item.proxy_wallet.or(item.user).or(item.maker),
+    let body: serde_json::Value =
+        serde_json::from_str(&text).map_err(|e| schema_error(&e.to_string()))?;
+
+    // Trades come as a plain array, or wrapped in `data` on some endpoints
+    let items = match &body {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Object(wrapped) => match wrapped.get("data") {
+            Some(serde_json::Value::Array(items)) => items,
+            _ => return Err(schema_error("expected an array of trades")),
+        },
+        _ => return Err(schema_error("expected an array of trades")),
+    };
+
+    let followed: Vec<serde_json::Value> = items
+        .iter()
+        .filter(|item| {
+            item.get("type")
+                .and_then(|t| t.as_str())
+                .is_none_or(|t| FOLLOWED_ACTIVITY.contains(&t))
+        })
+        .cloned()
+        .collect();
+
+    let page = types::parse_items(&followed, |item| {
+        ActivityItem::deserialize(item).ok()?.into_trade(item)
+    });
+    // Skipped activity still fills the page
+    Ok((page, items.len() >= PAGE_SIZE))
+}
+
+/// A CLOB outcome token and the market it belongs to.
+#[derive(Debug, Clone)]
+pub struct MarketToken {
+    pub asset_id: String,
+    pub question: Option<String>,
+    pub outcome: Option<String>,
+    pub outcome_index: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct GammaMarket {
+    #[serde(rename = "conditionId")]
+    condition_id: Option<String>,
+    #[serde(rename = "question")]
+    question: Option<String>,
+    // Gamma encodes these arrays as JSON strings
+    #[serde(rename = "clobTokenIds")]
+    clob_token_ids: Option<String>,
+    #[serde(rename = "outcomes")]
+    outcomes: Option<String>,
+    #[serde(rename = "slug")]
+    slug: Option<String>,
+    #[serde(rename = "endDate")]
+    end_date: Option<String>,
+    #[serde(rename = "category")]
+    category: Option<String>,
+    // Numbers sometimes arrive as strings
+    #[serde(rename = "liquidity")]
+    liquidity: Option<serde_json::Value>,
+    #[serde(rename = "volume24hr")]
+    volume_24hr: Option<serde_json::Value>,
+    #[serde(rename = "events", default)]
+    events: Vec<GammaEvent>,
+    #[serde(rename = "tags", default)]
+    tags: Vec<GammaTag>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GammaEvent {
+    slug: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GammaTag {
+    label: Option<String>,
+}
+
+fn lenient_f64(value: &Option<serde_json::Value>) -> Option<f64> {
+    match value.as_ref()? {
+        serde_json::Value::Number(n) => n.as_f64(),
+        serde_json::Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+async fn fetch_gamma_markets(
+    http: &HttpClient,
+    gamma_api_url: &str,
+    query: &[(&str, String)],
+) -> Result<Vec<GammaMarket>, PolymarketError> {
+    let url = format!("{}/markets", gamma_api_url);
+
+    let response = http
+        .send(|client| {
+            client
+                .get(&url)
+                .query(query)
+                .header("Accept", "application/json")
+        })
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(PolymarketError::ParseError(format!(
+            "API returned status: {}",
+            response.status()
+        )));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| PolymarketError::ParseError(e.to_string()))
+}
+
+/// Markets subscribed to when streaming without an explicit market list.
+pub const STREAM_ALL_MARKETS_LIMIT: usize = 500;
+
+/// Look up the outcome tokens for the given condition IDs.
+///
+/// With no condition IDs, returns the tokens of the most active open markets.
+pub async fn fetch_market_tokens(
+    http: &HttpClient,
+    gamma_api_url: &str,
+    condition_ids: &[String],
+) -> Result<Vec<MarketToken>, PolymarketError> {
+    let mut query: Vec<(&str, String)> = Vec::new();
+    if condition_ids.is_empty() {
+        query.push(("active", "true".to_string()));
+        query.push(("closed", "false".to_string()));
+        query.push(("order", "volume24hr".to_string()));
+        query.push(("ascending", "false".to_string()));
+        query.push(("limit", STREAM_ALL_MARKETS_LIMIT.to_string()));
+    } else {
+        for id in condition_ids {
+            query.push(("condition_ids", id.clone()));
+        }
+    }
+
+    let markets = fetch_gamma_markets(http, gamma_api_url, &query).await?;
+
+    let mut tokens = Vec::new();
+    for market in markets {
+        let token_ids: Vec<String> = market
+            .clob_token_ids
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+        let outcomes: Vec<String> = market
+            .outcomes
+            .as_deref()
+            .and_then(|s| serde_json::from_str(s).ok())
+            .unwrap_or_default();
+
+        for (i, asset_id) in token_ids.into_iter().enumerate() {
+            tokens.push(MarketToken {
+                asset_id,
+                question: market.question.clone(),
+                outcome: outcomes.get(i).cloned(),
+                outcome_index: i,
+            });
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Turn market slugs into condition IDs; values that already are condition
+/// IDs are kept as they are.
+pub async fn resolve_condition_ids(
+    http: &HttpClient,
+    gamma_api_url: &str,
+    markets: &[String],
+) -> Result<Vec<String>, PolymarketError> {
+    let slugs: Vec<&String> = markets.iter().filter(|m| !m.starts_with("0x")).collect();
+    if slugs.is_empty() {
+        return Ok(markets.to_vec());
+    }
+
+    let query: Vec<(&str, String)> = slugs.iter().map(|s| ("slug", s.to_string())).collect();
+    let found = fetch_gamma_markets(http, gamma_api_url, &query).await?;
+
+    markets
+        .iter()
+        .map(|market| {
+            if market.starts_with("0x") {
+                return Ok(market.clone());
+            }
+            found
+                .iter()
+                .find(|m| m.slug.as_deref() == Some(market.as_str()))
+                .and_then(|m| m.condition_id.clone())
+                .ok_or_else(|| {
+                    PolymarketError::ParseError(format!("Unknown market slug: {}", market))
                 })
-            })
+        })
+        .collect()
+}
+
+/// Market details from the Gamma API, used to enrich alerts.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MarketDetails {
+    pub condition_id: String,
+    pub end_date: Option<String>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub liquidity: Option<f64>,
+    pub volume_24hr: Option<f64>,
+    pub slug: Option<String>,
+    pub event_slug: Option<String>,
+    #[serde(default)]
+    pub outcomes: Vec<String>,
+}
+
+impl MarketDetails {
+    pub fn info(&self) -> MarketInfo {
+        MarketInfo {
+            end_date: self.end_date.clone(),
+            category: self.category.clone(),
+            tags: self.tags.clone(),
+            liquidity: self.liquidity,
+            volume_24h: self.volume_24hr,
+            event: self.event_slug.clone(),
+            url: self.url(),
+            outcomes: self.outcomes.clone(),
+        }
+    }
+
+    /// Link to the market on polymarket.com.
+    pub fn url(&self) -> Option<String> {
+        match (&self.event_slug, &self.slug) {
+            (Some(event), Some(market)) if event != market => {
+                Some(format!("https://polymarket.com/event/{}/{}", event, market))
+            }
+            (Some(event), _) => Some(format!("https://polymarket.com/event/{}", event)),
+            (None, Some(market)) => Some(format!("https://polymarket.com/market/{}", market)),
+            (None, None) => None,
+        }
+    }
+}
+
+// Condition IDs per Gamma request, to keep the query string short
+const DETAILS_PER_REQUEST: usize = 50;
+
+/// Look up market details for the given condition IDs.
+///
+/// Unknown condition IDs are simply missing from the result.
+pub async fn fetch_market_details(
+    http: &HttpClient,
+    gamma_api_url: &str,
+    condition_ids: &[String],
+) -> Result<Vec<MarketDetails>, PolymarketError> {
+    let mut details = Vec::new();
+
+    for chunk in condition_ids.chunks(DETAILS_PER_REQUEST) {
+        let mut query: Vec<(&str, String)> = vec![("include_tag", "true".to_string())];
+        for id in chunk {
+            query.push(("condition_ids", id.clone()));
+        }
+
+        for market in fetch_gamma_markets(http, gamma_api_url, &query).await? {
+            let Some(condition_id) = market.condition_id.clone() else {
+                continue;
+            };
+
+            details.push(MarketDetails {
+                condition_id,
+                end_date: market.end_date.clone(),
+                category: market.category.clone(),
+                tags: market.tags.iter().filter_map(|t| t.label.clone()).collect(),
+                liquidity: lenient_f64(&market.liquidity),
+                volume_24hr: lenient_f64(&market.volume_24hr),
+                slug: market.slug.clone(),
+                event_slug: market.events.first().and_then(|e| e.slug.clone()),
+                outcomes: market
+                    .outcomes
+                    .as_deref()
+                    .and_then(|s| serde_json::from_str(s).ok())
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    Ok(details)
+}
+
+/// A wallet's position as the Data API reports it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct WalletPosition {
+    pub condition_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub outcome: Option<String>,
+    pub size: Decimal,
+    #[serde(default)]
+    pub avg_price: Option<Decimal>,
+    /// Latest price of the outcome; 1 or 0 once the market has resolved
+    #[serde(default)]
+    pub cur_price: Option<Decimal>,
+    #[serde(default)]
+    pub initial_value: Option<Decimal>,
+    #[serde(default)]
+    pub current_value: Option<Decimal>,
+    #[serde(default)]
+    pub cash_pnl: Option<Decimal>,
+    #[serde(default)]
+    pub percent_pnl: Option<Decimal>,
+    /// The market has resolved and the shares can be cashed in
+    #[serde(default)]
+    pub redeemable: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct PortfolioValue {
+    value: Decimal,
+}
+
+// Most positions the positions endpoint returns at once
+const POSITIONS_LIMIT: usize = 500;
+
+/// The wallet's positions, largest first.
+pub async fn fetch_wallet_positions(
+    http: &HttpClient,
+    api_url: &str,
+    wallet: &str,
+) -> Result<Vec<WalletPosition>, PolymarketError> {
+    let query = [
+        ("user", wallet.to_string()),
+        ("limit", POSITIONS_LIMIT.to_string()),
+        ("sortBy", "CURRENT".to_string()),
+    ];
+    get_data(http, &format!("{}/positions", api_url), &query).await
+}
+
+/// What the wallet's positions are worth in dollars.
+pub async fn fetch_portfolio_value(
+    http: &HttpClient,
+    api_url: &str,
+    wallet: &str,
+) -> Result<Option<Decimal>, PolymarketError> {
+    let query = [("user", wallet.to_string())];
+    let values: Vec<PortfolioValue> = get_data(http, &format!("{}/value", api_url), &query).await?;
+    Ok(values.first().map(|v| v.value))
+}
+
+async fn get_data<T: serde::de::DeserializeOwned>(
+    http: &HttpClient,
+    url: &str,
+    query: &[(&str, String)],
+) -> Result<T, PolymarketError> {
+    let response = http
+        .send(|client| {
+            client
+                .get(url)
+                .query(query)
+                .header("Accept", "application/json")
+        })
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(PolymarketError::ParseError(format!(
+            "API returned status: {}",
+            response.status()
+        )));
+    }
+
+    let text = response.text().await?;
+    serde_json::from_str(&text).map_err(|e| PolymarketError::SchemaError {
+        message: e.to_string(),
+        sample: types::payload_sample(&text),
+    })
+}
+
+/// Polymarket adapter: Data API trades, optionally the CLOB market channel,
+/// and Gamma market details.
+pub struct PolymarketSource {
+    http: HttpClient,
+    api_url: String,
+    gamma_api_url: String,
+    /// Market channel URL when streaming
+    ws_url: Option<String>,
+    /// Condition IDs to fetch trades for (default: all markets)
+    markets: Vec<String>,
+    /// Condition IDs to stream; REST trades are limited to them too while
+    /// filling in for the stream
+    stream_markets: Vec<String>,
+    /// Wallets whose activity is polled on top of the trade feed
+    followed: Vec<String>,
+    details: MarketCache<MarketDetails>,
+}
+
+impl PolymarketSource {
+    pub fn new(
+        http: HttpClient,
+        api_url: String,
+        gamma_api_url: String,
+        details: MarketCache<MarketDetails>,
+    ) -> Self {
+        Self {
+            http,
+            api_url,
+            gamma_api_url,
+            ws_url: None,
+            markets: Vec::new(),
+            stream_markets: Vec::new(),
+            followed: Vec::new(),
+            details,
+        }
+    }
+
+    /// Only watch trades in these markets.
+    pub fn with_markets(mut self, condition_ids: Vec<String>) -> Self {
+        self.markets = condition_ids;
+        self
+    }
+
+    /// Also poll each of these wallets' activity, which catches their trades
+    /// when the feed moves too fast to page back to them, along with their
+    /// splits, merges and redeems.
+    pub fn with_followed(mut self, wallets: Vec<String>) -> Self {
+        self.followed = wallets;
+        self
+    }
+
+    /// Stream trades for `markets` (default: the markets passed to
+    /// `with_markets`, or else the most active markets).
+    pub fn with_stream(mut self, ws_url: String, markets: Vec<String>) -> Self {
+        self.ws_url = Some(ws_url);
+        self.stream_markets = if markets.is_empty() {
+            self.markets.clone()
+        } else {
+            markets
+        };
+        self
+    }
+}
+
+impl TradeSource for PolymarketSource {
+    fn platform(&self) -> Platform {
+        Platform::Polymarket
+    }
+
+    async fn fetch_trades(
+        &mut self,
+        max_pages: usize,
+        stop_before: Option<i64>,
+    ) -> Result<TradeBatch<NormalizedTrade>, SourceError> {
+        let stop = |t: &Trade| source::is_older(t.timestamp_secs(), stop_before);
+        let mut batch = fetch_trades_until(
+            &self.http,
+            &self.api_url,
+            TradeFeed::Markets(&self.markets),
+            max_pages,
+            stop,
+        )
+        .await?;
+
+        if self.ws_url.is_some() && !self.stream_markets.is_empty() {
+            batch
+                .trades
+                .retain(|t| self.stream_markets.contains(&t.market));
+        }
+
+        // A followed wallet's trades usually turn up in the feed too; the
+        // watcher's dedupe drops the second copy. The first poll only looks
+        // back as far as the feed does, rather than over the wallet's history.
+        if !self.followed.is_empty() {
+            let since =
+                stop_before.or_else(|| batch.trades.iter().filter_map(Trade::timestamp_secs).min());
+            let stop = |t: &Trade| source::is_older(t.timestamp_secs(), since);
+            for wallet in &self.followed {
+                let feed = TradeFeed::Wallet(wallet);
+                match fetch_trades_until(&self.http, &self.api_url, feed, max_pages, stop).await {
+                    Ok(activity) => batch.merge(activity),
+                    // One wallet failing shouldn't cost the others their trades
+                    Err(e) => {
+                        eprintln!(
+                            "{} Polymarket activity for {} failed: {}",
+                            "[WARNING]".yellow(),
+                            wallet,
+                            e
+                        );
+                        batch.coverage_gap = true;
+                    }
+                }
+            }
+            batch
+                .trades
+                .sort_by_key(|t| std::cmp::Reverse(t.timestamp_secs()));
+        }
+
+        Ok(batch.map(Trade::normalize))
+    }
+
+    fn take_stream(&mut self) -> Option<mpsc::Receiver<StreamEvent<NormalizedTrade>>> {
+        let ws_url = self.ws_url.clone()?;
+        Some(crate::polymarket_ws::spawn_trade_stream(
+            self.http.clone(),
+            ws_url,
+            self.gamma_api_url.clone(),
+            self.stream_markets.clone(),
+        ))
+    }
+
+    // Streaming "all markets" only covers the most active ones, and streamed
+    // trades carry no wallet: polls pick it up from the REST copy
+    fn polls_while_streaming(&self) -> bool {
+        self.ws_url.is_some()
+    }
+
+    async fn enrich(&mut self, trades: &mut [NormalizedTrade]) {
+        let missing = self
+            .details
+            .missing(trades.iter().map(|t| t.market_id.as_str()));
+
+        if !missing.is_empty() {
+            match fetch_market_details(&self.http, &self.gamma_api_url, &missing).await {
+                Ok(found) => {
+                    for market in found {
+                        self.details.insert(market.condition_id.clone(), market);
+                    }
+                }
+                Err(e) => {
+                    eprintln!(
+                        "{} Polymarket market lookup failed: {}",
+                        "[WARNING]".yellow(),
+                        e
+                    );
+                }
+            }
+        }
+
+        for trade in trades {
+            trade.market = self.details.get(&trade.market_id).map(MarketDetails::info);
+
+            // Name the outcome exposure is measured on, for trades of the other one
+            let first_outcome = trade.market.as_ref().and_then(|m| m.outcomes.first());
+            if let Some(exposure) = trade.exposure.as_mut() {
+                exposure.outcome = exposure.outcome.take().or(first_outcome.cloned());
+            }
+        }
+    }
+
+    fn save(&mut self) -> std::io::Result<()> {
+        self.details.save()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::http::HttpSettings;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const TRADES: &str = include_str!("../tests/fixtures/polymarket/trades.json");
+    const ACTIVITY: &str = include_str!("../tests/fixtures/polymarket/activity.json");
+
+    // Answer every request with `respond(path and query)`; returns the base URL
+    async fn serve(respond: fn(&str) -> (u16, String)) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let target = request.split_whitespace().nth(1).unwrap_or_default();
+                let (status, body) = respond(target);
+                let response = format!(
+                    "HTTP/1.1 {} \r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    fn source(api_url: String) -> PolymarketSource {
+        let http = HttpClient::new(HttpSettings::from_config(&Config::default()));
+        PolymarketSource::new(http, api_url.clone(), api_url, MarketCache::new(3600))
+    }
+
+    #[tokio::test]
+    async fn failing_wallet_keeps_the_rest() {
+        let url = serve(|target| {
+            if target.starts_with("/trades") {
+                (200, TRADES.to_string())
+            } else if target.contains("user=0xgood") {
+                (200, ACTIVITY.to_string())
+            } else {
+                (404, "{}".to_string())
+            }
+        })
+        .await;
+        let mut source = source(url).with_followed(vec!["0xbad".to_string(), "0xgood".to_string()]);
+
+        let batch = source.fetch_trades(1, None).await.unwrap();
+
+        let wallets: Vec<&str> = batch
+            .trades
+            .iter()
+            .filter_map(|t| t.actor_id.as_deref())
             .collect();
-        return Ok(trades);
+        assert_eq!(wallets, ["0xgood", "0xfeed", "0xgood"]);
+        // The failed wallet's activity is missing, so the poll has a gap
+        assert!(batch.coverage_gap);
+    }
+
+    #[tokio::test]
+    async fn skipped_activity_isnt_counted_as_received() {
+        let url = serve(|_| (200, ACTIVITY.to_string())).await;
+        let http = HttpClient::new(HttpSettings::from_config(&Config::default()));
+
+        let batch = fetch_trades_until(&http, &url, TradeFeed::Wallet("0xgood"), 5, |_| false)
+            .await
+            .unwrap();
+
+        // The reward is neither a trade nor a format problem
+        assert_eq!(batch.trades.len(), 2);
+        assert_eq!(batch.received, 2);
+        assert_eq!(batch.dropped, 0);
+        assert_eq!(batch.pages, 1);
     }
 
-    // If parsing fails, return empty list rather than error
-    // This allows the tool to continue working even if Polymarket API format changes
-    Ok(Vec::new())
+    #[tokio::test]
+    async fn page_full_of_skipped_activity_pages_on() {
+        let url = serve(|target| {
+            if target.contains("offset=0") {
+                let reward = serde_json::json!({"type": "REWARD", "transactionHash": "0xr"});
+                (
+                    200,
+                    serde_json::to_string(&vec![reward; PAGE_SIZE]).unwrap(),
+                )
+            } else {
+                (200, ACTIVITY.to_string())
+            }
+        })
+        .await;
+        let http = HttpClient::new(HttpSettings::from_config(&Config::default()));
+
+        let batch = fetch_trades_until(&http, &url, TradeFeed::Wallet("0xgood"), 5, |_| false)
+            .await
+            .unwrap();
+
+        assert_eq!(batch.pages, 2);
+        assert_eq!(batch.trades.len(), 2);
+        assert!(!batch.coverage_gap);
+    }
 }
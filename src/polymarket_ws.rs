@@ -0,0 +1,379 @@
+// Polymarket CLOB WebSocket market channel
+//
+// Subscribes to the market channel for a set of outcome tokens and turns
+// `last_trade_price` events into normalized trades, reconnecting with
+// exponential backoff when the socket drops.
+
+use crate::http::HttpClient;
+use crate::polymarket::{self, MarketToken, Trade};
+use crate::types::{
+    NormalizedTrade, StreamEvent, TradeKind, STREAM_INITIAL_BACKOFF, STREAM_MAX_BACKOFF,
+};
+use futures_util::{SinkExt, StreamExt};
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+// The CLOB drops idle connections, so send an application-level PING
+const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct LastTradePrice {
+    event_type: String,
+    asset_id: String,
+    market: String,
+    price: String,
+    size: String,
+    side: String,
+    timestamp: String,
+}
+
+impl LastTradePrice {
+    fn into_trade(
+        self,
+        tokens: &HashMap<String, MarketToken>,
+        raw: serde_json::Value,
+    ) -> Option<Trade> {
+        let price: Decimal = self.price.parse().ok()?;
+        let size: Decimal = self.size.parse().ok()?;
+        let timestamp_ms: i64 = self.timestamp.parse().ok()?;
+        let token = tokens.get(&self.asset_id);
+
+        Some(Trade {
+            // The market channel carries no transaction hash
+            id: format!(
+                "ws-{}-{}-{}-{}",
+                self.asset_id, timestamp_ms, self.price, self.size
+            ),
+            market: self.market,
+            asset_id: self.asset_id,
+            side: self.side,
+            kind: TradeKind::Trade,
+            size,
+            price,
+            timestamp: chrono::DateTime::from_timestamp_millis(timestamp_ms)
+                .map(|dt| dt.to_rfc3339())
+                .unwrap_or_default(),
+            market_title: token.and_then(|t| t.question.clone()),
+            outcome: token.and_then(|t| t.outcome.clone()),
+            wallet_id: None,
+            outcome_index: token.map(|t| t.outcome_index),
+            raw,
+        })
+    }
+}
+
+/// Spawn a background task that keeps a market channel subscription open.
+///
+/// `condition_ids` selects the markets to follow; when empty, the most active
+/// open markets are used. The task runs until the receiver is dropped.
+pub fn spawn_trade_stream(
+    http: HttpClient,
+    url: String,
+    gamma_api_url: String,
+    condition_ids: Vec<String>,
+) -> mpsc::Receiver<StreamEvent<NormalizedTrade>> {
+    let (tx, rx) = mpsc::channel(1024);
+
+    tokio::spawn(async move {
+        let mut backoff = STREAM_INITIAL_BACKOFF;
+
+        loop {
+            let reason = match run_session(
+                &http,
+                &url,
+                &gamma_api_url,
+                &condition_ids,
+                &tx,
+                &mut backoff,
+            )
+            .await
+            {
+                Ok(()) => "connection closed by server".to_string(),
+                Err(e) => e,
+            };
+
+            if tx.send(StreamEvent::Disconnected(reason)).await.is_err() {
+                return;
+            }
+
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(STREAM_MAX_BACKOFF);
+        }
+    });
+
+    rx
+}
+
+async fn run_session(
+    http: &HttpClient,
+    url: &str,
+    gamma_api_url: &str,
+    condition_ids: &[String],
+    tx: &mpsc::Sender<StreamEvent<NormalizedTrade>>,
+    backoff: &mut Duration,
+) -> Result<(), String> {
+    // Refresh the token list on every connect so "all markets" stays current
+    let tokens: HashMap<String, MarketToken> =
+        polymarket::fetch_market_tokens(http, gamma_api_url, condition_ids)
+            .await
+            .map_err(|e| format!("market lookup failed: {}", e))?
+            .into_iter()
+            .map(|t| (t.asset_id.clone(), t))
+            .collect();
+
+    if tokens.is_empty() {
+        return Err("no outcome tokens found for the selected markets".to_string());
+    }
+
+    let (mut socket, _) = tokio_tungstenite::connect_async(url)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let subscribe = json!({
+        "type": "market",
+        "assets_ids": tokens.keys().collect::<Vec<_>>(),
+    });
+    socket
+        .send(Message::Text(subscribe.to_string()))
+        .await
+        .map_err(|e| e.to_string())?;
+
+    *backoff = STREAM_INITIAL_BACKOFF;
+    if tx.send(StreamEvent::Connected).await.is_err() {
+        return Ok(());
+    }
+
+    let mut ping = tokio::time::interval(PING_INTERVAL);
+
+    loop {
+        let message = tokio::select! {
+            _ = ping.tick() => {
+                socket
+                    .send(Message::Text("PING".to_string()))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+            message = socket.next() => match message {
+                Some(message) => message.map_err(|e| e.to_string())?,
+                None => return Ok(()),
+            },
+        };
+
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Ping(payload) => {
+                socket
+                    .send(Message::Pong(payload))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+            Message::Close(_) => return Ok(()),
+            _ => continue,
+        };
+
+        // Events arrive either as a single object or as an array
+        let events = match serde_json::from_str::<serde_json::Value>(&text) {
+            Ok(serde_json::Value::Array(events)) => events,
+            Ok(event) => vec![event],
+            Err(_) => continue,
+        };
+
+        for event in events {
+            let Ok(parsed) = LastTradePrice::deserialize(&event) else {
+                continue;
+            };
+            if parsed.event_type != "last_trade_price" {
+                continue;
+            }
+            if let Some(trade) = parsed.into_trade(&tokens, event) {
+                if tx
+                    .send(StreamEvent::Trade(trade.normalize()))
+                    .await
+                    .is_err()
+                {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::http::HttpSettings;
+    use crate::types::Side;
+    use rust_decimal_macros::dec;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const MARKETS: &str = include_str!("../tests/fixtures/polymarket/markets.json");
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn event(event_type: &str, asset_id: &str, price: &str, ts: i64) -> serde_json::Value {
+        json!({
+            "event_type": event_type,
+            "asset_id": asset_id,
+            "market": "0xc0ffee",
+            "price": price,
+            "size": "500",
+            "side": "BUY",
+            "timestamp": ts.to_string(),
+        })
+    }
+
+    // Gamma's /markets, answering every lookup with the fixture
+    async fn serve_gamma(targets: mpsc::UnboundedSender<String>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = Vec::new();
+                let mut buf = [0u8; 4096];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = socket.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+                let request = String::from_utf8_lossy(&request);
+                let target = request.split_whitespace().nth(1).unwrap_or_default();
+                targets.send(target.to_string()).unwrap();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    MARKETS.len(),
+                    MARKETS
+                );
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        url
+    }
+
+    async fn next_event(
+        rx: &mut mpsc::Receiver<StreamEvent<NormalizedTrade>>,
+    ) -> StreamEvent<NormalizedTrade> {
+        tokio::time::timeout(TIMEOUT, rx.recv())
+            .await
+            .expect("timed out waiting for a stream event")
+            .expect("stream task ended")
+    }
+
+    async fn next_trade(rx: &mut mpsc::Receiver<StreamEvent<NormalizedTrade>>) -> NormalizedTrade {
+        match next_event(rx).await {
+            StreamEvent::Trade(trade) => trade,
+            _ => panic!("expected a trade"),
+        }
+    }
+
+    #[tokio::test]
+    async fn subscribes_decodes_and_reconnects() {
+        let (targets_tx, mut targets) = mpsc::unbounded_channel();
+        let gamma_url = serve_gamma(targets_tx).await;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}/ws/market", listener.local_addr().unwrap());
+        let (subscribes_tx, mut subscribes) = mpsc::unbounded_channel::<String>();
+
+        tokio::spawn(async move {
+            for session in 0..2 {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+                let Some(Ok(Message::Text(subscribe))) = socket.next().await else {
+                    panic!("expected a subscribe message");
+                };
+                subscribes_tx.send(subscribe).unwrap();
+
+                if session == 0 {
+                    // Another event type, then a trade, together in one array
+                    let batch = json!([
+                        event("price_change", "111", "0.91", 1700000000000),
+                        event("last_trade_price", "222", "0.10", 1700000000000),
+                    ]);
+                    socket.send(Message::Text(batch.to_string())).await.unwrap();
+                    // A lone event, for a token that isn't subscribed
+                    let unknown = event("last_trade_price", "333", "0.50", 1700000001000);
+                    socket
+                        .send(Message::Text(unknown.to_string()))
+                        .await
+                        .unwrap();
+                    // Drop the socket without a close frame
+                    drop(socket);
+                } else {
+                    let trade = event("last_trade_price", "111", "0.90", 1700000060000);
+                    socket.send(Message::Text(trade.to_string())).await.unwrap();
+                    // Hold the connection open until the test ends
+                    while socket.next().await.is_some() {}
+                }
+            }
+        });
+
+        let http = HttpClient::new(HttpSettings::from_config(&Config::default()));
+        let mut rx = spawn_trade_stream(http, url, gamma_url, vec!["0xc0ffee".to_string()]);
+
+        assert!(matches!(next_event(&mut rx).await, StreamEvent::Connected));
+        let target = targets.recv().await.unwrap();
+        assert_eq!(target, "/markets?condition_ids=0xc0ffee");
+        let subscribe: serde_json::Value =
+            serde_json::from_str(&subscribes.recv().await.unwrap()).unwrap();
+        assert_eq!(subscribe["type"], "market");
+        let mut assets: Vec<String> =
+            serde_json::from_value(subscribe["assets_ids"].clone()).unwrap();
+        assets.sort();
+        assert_eq!(assets, ["111", "222"]);
+
+        // Buying No at 0.10 is shedding Yes at 0.90
+        let trade = next_trade(&mut rx).await;
+        assert_eq!(
+            trade.trade_id,
+            "ws-222-1700000000000-0.10-500:222:BUY:500:0.1:"
+        );
+        assert_eq!(trade.market_id, "0xc0ffee");
+        assert_eq!(
+            trade.market_title.as_deref(),
+            Some("Will it rain in London tomorrow?")
+        );
+        assert_eq!(trade.outcome_id, "222");
+        assert_eq!(trade.outcome.as_deref(), Some("No"));
+        assert_eq!(trade.side, Side::Buy);
+        assert_eq!(trade.price, dec!(0.10));
+        assert_eq!(trade.notional, dec!(50));
+        assert_eq!(trade.actor_id, None);
+        assert_eq!(trade.timestamp.timestamp(), 1_700_000_000);
+        let exposure = trade.exposure.unwrap();
+        assert_eq!(exposure.contracts, dec!(-500));
+        assert_eq!(exposure.probability, dec!(0.90));
+
+        // A token missing from the lookup still streams, just without names
+        let trade = next_trade(&mut rx).await;
+        assert_eq!(trade.outcome_id, "333");
+        assert_eq!(trade.outcome, None);
+        assert!(trade.exposure.is_none());
+
+        // The drop is reported, so the watcher can poll for what it misses
+        assert!(matches!(
+            next_event(&mut rx).await,
+            StreamEvent::Disconnected(_)
+        ));
+
+        // After the backoff it looks the tokens up again and resubscribes
+        assert!(matches!(next_event(&mut rx).await, StreamEvent::Connected));
+        assert_eq!(targets.recv().await.unwrap(), target);
+        assert!(subscribes.recv().await.unwrap().contains("\"market\""));
+
+        let trade = next_trade(&mut rx).await;
+        assert_eq!(trade.outcome.as_deref(), Some("Yes"));
+        assert_eq!(trade.price, dec!(0.90));
+        assert_eq!(trade.exposure.unwrap().contracts, dec!(500));
+    }
+}
@@ -0,0 +1,311 @@
+// What each wallet holds
+//
+// Every trade with a known trader is booked against that trader's holding
+// in the outcome traded: buys add contracts at their price, sells take them
+// off at the average entry price and realize the difference. Holdings are
+// marked to the latest price any trader paid for the outcome.
+//
+// Unlike the exposure used for exit detection, outcomes aren't netted
+// against each other: YES and NO bought in the same market are two holdings.
+
+use crate::types::{NormalizedTrade, Side, TradeKind};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Closed holdings and prices untouched for this long (in trade time) are
+/// forgotten. Open holdings are kept until they're sold.
+pub const RETENTION_SECS: i64 = 7 * 86400;
+
+/// One wallet's holding in one outcome.
+#[derive(Debug, Clone, Serialize)]
+pub struct Holding {
+    pub market_id: String,
+    pub outcome_id: String,
+    pub outcome: Option<String>,
+    pub market_title: Option<String>,
+    pub contracts: Decimal,
+    /// Dollars paid for the contracts still held
+    pub cost_basis: Decimal,
+    /// Dollars gained (or lost) on contracts already sold
+    pub realized_pnl: Decimal,
+    /// Latest price paid for the outcome by anyone
+    pub mark_price: Decimal,
+    /// Dollars one contract pays if the outcome wins ($1, or the mana rate on
+    /// Manifold)
+    pub payout: Decimal,
+    /// Time of the last trade in the holding (unix seconds)
+    pub updated: i64,
+}
+
+impl Holding {
+    /// Average price paid per contract still held.
+    pub fn avg_price(&self) -> Decimal {
+        self.cost_basis
+            .checked_div(self.contracts * self.payout)
+            .unwrap_or_default()
+    }
+
+    /// Dollar value at the mark price.
+    pub fn market_value(&self) -> Decimal {
+        self.contracts * self.mark_price * self.payout
+    }
+
+    pub fn unrealized_pnl(&self) -> Decimal {
+        self.market_value() - self.cost_basis
+    }
+
+    /// Unrealized gain as a percentage of the cost basis.
+    pub fn return_percent(&self) -> Option<Decimal> {
+        self.unrealized_pnl()
+            .checked_div(self.cost_basis)
+            .map(|r| r * Decimal::ONE_HUNDRED)
+    }
+
+    pub fn is_open(&self) -> bool {
+        !self.contracts.is_zero()
+    }
+}
+
+// Market and outcome ID
+type OutcomeKey = (String, String);
+
+#[derive(Default)]
+pub struct PositionBook {
+    // Wallet to its holdings
+    holdings: HashMap<String, HashMap<OutcomeKey, Holding>>,
+    // Latest price and trade time of each outcome
+    marks: HashMap<OutcomeKey, (Decimal, i64)>,
+}
+
+impl PositionBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Mark the trade's outcome to its price and, when the trader is known,
+    /// book it. Returns the trader's holding after the trade.
+    pub fn record(&mut self, trade: &NormalizedTrade) -> Option<Holding> {
+        // Splits, merges and redeems move every outcome at once and say
+        // nothing about any one outcome's price
+        if trade.kind != TradeKind::Trade {
+            return None;
+        }
+
+        let key = (trade.market_id.clone(), trade.outcome_id.clone());
+        let timestamp = trade.timestamp.timestamp();
+        self.set_mark(key.clone(), trade.price, timestamp);
+        let mark_price = self.marks[&key].0;
+
+        let actor = trade.actor_id.as_ref()?;
+        let holdings = self.holdings.entry(actor.clone()).or_default();
+        let holding = holdings.entry(key.clone()).or_insert_with(|| Holding {
+            market_id: trade.market_id.clone(),
+            outcome_id: trade.outcome_id.clone(),
+            outcome: None,
+            market_title: None,
+            contracts: Decimal::ZERO,
+            cost_basis: Decimal::ZERO,
+            realized_pnl: Decimal::ZERO,
+            mark_price,
+            payout: Decimal::ONE,
+            updated: timestamp,
+        });
+
+        if trade.outcome.is_some() {
+            holding.outcome = trade.outcome.clone();
+        }
+        if trade.market_title.is_some() {
+            holding.market_title = trade.market_title.clone();
+        }
+        // Notional over contracts times price is 1 except on Manifold
+        if let Some(payout) = trade.notional.checked_div(trade.contracts * trade.price) {
+            if !payout.is_zero() {
+                holding.payout = payout;
+            }
+        }
+        holding.mark_price = mark_price;
+        holding.updated = holding.updated.max(timestamp);
+
+        match trade.side {
+            Side::Buy => {
+                holding.contracts += trade.contracts;
+                holding.cost_basis += trade.notional;
+            }
+            Side::Sell => {
+                // Contracts bought before tracking started have no known
+                // cost, so only the ones booked here are matched
+                let matched = trade.contracts.min(holding.contracts);
+                if !matched.is_zero() {
+                    let basis = holding.cost_basis * matched / holding.contracts;
+                    let proceeds = trade.notional * matched / trade.contracts;
+                    holding.realized_pnl += proceeds - basis;
+                    holding.cost_basis -= basis;
+                    holding.contracts -= matched;
+                }
+            }
+        }
+
+        // Nothing was booked: a sale of contracts bought before tracking
+        if !holding.is_open() && holding.realized_pnl.is_zero() {
+            holdings.remove(&key);
+            return None;
+        }
+        Some(holding.clone())
+    }
+
+    /// The wallet's holdings, marked to the latest prices, most valuable
+    /// first. Closed holdings with realized PnL are included.
+    pub fn holdings(&self, wallet_id: &str) -> Vec<Holding> {
+        let mut holdings: Vec<Holding> = self
+            .holdings
+            .get(wallet_id)
+            .into_iter()
+            .flat_map(|h| h.iter())
+            .map(|(key, holding)| {
+                let mut holding = holding.clone();
+                if let Some((price, _)) = self.marks.get(key) {
+                    holding.mark_price = *price;
+                }
+                holding
+            })
+            .collect();
+        holdings.sort_by_key(|h| std::cmp::Reverse(h.market_value()));
+        holdings
+    }
+
+    /// Restore a saved holding.
+    pub fn insert(&mut self, wallet_id: String, holding: Holding) {
+        let key = (holding.market_id.clone(), holding.outcome_id.clone());
+        self.holdings
+            .entry(wallet_id)
+            .or_default()
+            .insert(key, holding);
+    }
+
+    /// Restore a saved price. Older prices don't replace newer ones.
+    pub fn set_mark(&mut self, key: (String, String), price: Decimal, timestamp: i64) {
+        let mark = self.marks.entry(key).or_insert((price, timestamp));
+        if timestamp >= mark.1 {
+            *mark = (price, timestamp);
+        }
+    }
+
+    /// Forget closed holdings and prices not traded in `RETENTION_SECS` up
+    /// to `latest`.
+    pub fn sweep(&mut self, latest: i64) {
+        for holdings in self.holdings.values_mut() {
+            holdings.retain(|_, h| h.is_open() || latest - h.updated < RETENTION_SECS);
+        }
+        self.holdings.retain(|_, holdings| !holdings.is_empty());
+        self.marks
+            .retain(|_, (_, ts)| latest - *ts < RETENTION_SECS);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Platform;
+    use rust_decimal_macros::dec;
+
+    const T0: i64 = 1_700_000_000;
+
+    fn trade(
+        actor: Option<&str>,
+        market: &str,
+        side: Side,
+        contracts: Decimal,
+        price: Decimal,
+        ts: i64,
+    ) -> NormalizedTrade {
+        NormalizedTrade {
+            platform: Platform::Polymarket,
+            trade_id: format!("{}:{}", market, ts),
+            market_id: market.to_string(),
+            market_title: None,
+            outcome_id: format!("{}-yes", market),
+            outcome: Some("Yes".to_string()),
+            side,
+            kind: TradeKind::Trade,
+            contracts,
+            price,
+            notional: contracts * price,
+            cost: None,
+            max_payout: None,
+            exposure: None,
+            actor_id: actor.map(str::to_string),
+            timestamp: chrono::DateTime::from_timestamp(ts, 0).unwrap(),
+            market: None,
+            raw: serde_json::Value::Null,
+        }
+    }
+
+    #[test]
+    fn sweep_keeps_open_holdings() {
+        let mut book = PositionBook::new();
+        book.record(&trade(
+            Some("0xholder"),
+            "m1",
+            Side::Buy,
+            dec!(100),
+            dec!(0.40),
+            T0,
+        ));
+        book.record(&trade(
+            Some("0xtrader"),
+            "m2",
+            Side::Buy,
+            dec!(100),
+            dec!(0.40),
+            T0,
+        ));
+        book.record(&trade(
+            Some("0xtrader"),
+            "m2",
+            Side::Sell,
+            dec!(100),
+            dec!(0.50),
+            T0,
+        ));
+
+        book.sweep(T0 + RETENTION_SECS);
+
+        // The open holding outlives its mark and keeps the last price it saw
+        let held = book.holdings("0xholder");
+        assert_eq!(held.len(), 1);
+        assert_eq!(held[0].contracts, dec!(100));
+        assert_eq!(held[0].mark_price, dec!(0.40));
+        assert!(book.marks.is_empty());
+
+        // The closed one is gone
+        assert!(book.holdings("0xtrader").is_empty());
+    }
+
+    #[test]
+    fn recent_closed_holdings_are_kept() {
+        let mut book = PositionBook::new();
+        book.record(&trade(
+            Some("0xtrader"),
+            "m2",
+            Side::Buy,
+            dec!(100),
+            dec!(0.40),
+            T0,
+        ));
+        book.record(&trade(
+            Some("0xtrader"),
+            "m2",
+            Side::Sell,
+            dec!(100),
+            dec!(0.50),
+            T0,
+        ));
+
+        book.sweep(T0 + RETENTION_SECS - 1);
+
+        let closed = book.holdings("0xtrader");
+        assert_eq!(closed.len(), 1);
+        assert_eq!(closed[0].realized_pnl, dec!(10));
+    }
+}
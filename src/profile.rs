@@ -0,0 +1,82 @@
+// Wallet profiles
+//
+// Summaries for `wwatcher wallet`, worked out from a wallet's recent
+// activity and positions as the Polymarket Data API returns them: how the
+// wallet did on markets that resolved, and where it trades the most.
+
+use crate::polymarket::WalletPosition;
+use crate::types::{NormalizedTrade, TradeKind};
+use rust_decimal::Decimal;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Resolved markets the wallet held shares in at resolution.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Record {
+    pub wins: usize,
+    pub losses: usize,
+}
+
+impl Record {
+    /// A market is won if any shares the wallet redeemed there paid out, or
+    /// any shares it has yet to redeem are worth $1. Each market counts once.
+    pub fn from_activity(activity: &[NormalizedTrade], positions: &[WalletPosition]) -> Self {
+        let mut won: HashMap<&str, bool> = HashMap::new();
+        for redeem in activity.iter().filter(|t| t.kind == TradeKind::Redeem) {
+            *won.entry(&redeem.market_id).or_default() |= redeem.notional > Decimal::ZERO;
+        }
+        for position in positions.iter().filter(|p| p.redeemable) {
+            let paid = position.cur_price.is_some_and(|p| p > Decimal::ZERO);
+            *won.entry(&position.condition_id).or_default() |= paid;
+        }
+
+        let wins = won.values().filter(|w| **w).count();
+        Self {
+            wins,
+            losses: won.len() - wins,
+        }
+    }
+
+    /// Share of the resolved markets won, as a percentage.
+    pub fn win_percent(&self) -> Option<Decimal> {
+        Decimal::from(self.wins)
+            .checked_div(Decimal::from(self.wins + self.losses))
+            .map(|r| r * Decimal::ONE_HUNDRED)
+    }
+}
+
+/// The wallet's trading in one market.
+#[derive(Debug, Clone, Serialize)]
+pub struct MarketVolume {
+    pub market_id: String,
+    pub market_title: Option<String>,
+    pub trades: usize,
+    /// Dollars bought and sold
+    pub volume: Decimal,
+}
+
+/// The `limit` markets the wallet traded the most dollars in. Splits,
+/// merges and redeems aren't trades, so they don't count.
+pub fn top_markets(activity: &[NormalizedTrade], limit: usize) -> Vec<MarketVolume> {
+    let mut markets: HashMap<&str, MarketVolume> = HashMap::new();
+    for trade in activity.iter().filter(|t| t.kind == TradeKind::Trade) {
+        let market = markets
+            .entry(&trade.market_id)
+            .or_insert_with(|| MarketVolume {
+                market_id: trade.market_id.clone(),
+                market_title: None,
+                trades: 0,
+                volume: Decimal::ZERO,
+            });
+        if market.market_title.is_none() {
+            market.market_title = trade.market_title.clone();
+        }
+        market.trades += 1;
+        market.volume += trade.notional;
+    }
+
+    let mut markets: Vec<MarketVolume> = markets.into_values().collect();
+    markets.sort_by_key(|m| std::cmp::Reverse(m.volume));
+    markets.truncate(limit);
+    markets
+}
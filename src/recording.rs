@@ -0,0 +1,298 @@
+// Recording and replay of raw API responses
+//
+// `watch --record <dir>` saves every API response the watcher receives, with
+// the time it arrived. `wwatcher replay <dir>` serves those responses back in
+// place of the network, at the pace they were recorded (or faster), so a
+// session runs through the same dedupe, wallet tracking and anomaly checks.
+
+use crate::config::Endpoints;
+use chrono::{DateTime, Utc};
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+const SESSION_FILE: &str = "session.json";
+const RESPONSES_FILE: &str = "responses.jsonl";
+
+// Time left after the last response for the pipeline to finish with it
+const REPLAY_DRAIN: Duration = Duration::from_secs(2);
+
+#[derive(Error, Debug)]
+pub enum RecordingError {
+    #[error("{0}: {1}")]
+    Io(String, std::io::Error),
+    #[error("{0} is not a valid recording: {1}")]
+    Invalid(String, String),
+}
+
+/// How a recording was made.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub started_at: DateTime<Utc>,
+    /// The command line, without the program name
+    pub args: Vec<String>,
+    /// The base URLs the responses came from
+    pub endpoints: Endpoints,
+}
+
+impl Session {
+    pub fn load(dir: &Path) -> Result<Self, RecordingError> {
+        let path = dir.join(SESSION_FILE);
+        let text = fs::read_to_string(&path)
+            .map_err(|e| RecordingError::Io(path.display().to_string(), e))?;
+        serde_json::from_str(&text)
+            .map_err(|e| RecordingError::Invalid(dir.display().to_string(), e.to_string()))
+    }
+}
+
+/// An API response as it was received.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedResponse {
+    pub at: DateTime<Utc>,
+    pub url: String,
+    pub status: u16,
+    pub body: String,
+}
+
+/// Appends every response to the recording as it arrives, so an interrupted
+/// session keeps what it saw.
+pub struct Recorder {
+    file: std::sync::Mutex<File>,
+}
+
+impl Recorder {
+    /// Start a recording in `dir`, replacing any recording already there.
+    pub fn create(dir: &Path, session: &Session) -> Result<Self, RecordingError> {
+        let io_error = |path: &Path, e| RecordingError::Io(path.display().to_string(), e);
+
+        fs::create_dir_all(dir).map_err(|e| io_error(dir, e))?;
+
+        let session_path = dir.join(SESSION_FILE);
+        let json = serde_json::to_string_pretty(session)
+            .map_err(|e| RecordingError::Invalid(dir.display().to_string(), e.to_string()))?;
+        fs::write(&session_path, json).map_err(|e| io_error(&session_path, e))?;
+
+        let responses_path = dir.join(RESPONSES_FILE);
+        let file = File::create(&responses_path).map_err(|e| io_error(&responses_path, e))?;
+
+        Ok(Self {
+            file: std::sync::Mutex::new(file),
+        })
+    }
+
+    pub fn record(&self, response: &RecordedResponse) {
+        let Ok(line) = serde_json::to_string(response) else {
+            return;
+        };
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        if let Err(e) = writeln!(file, "{}", line) {
+            eprintln!("{} Failed to record response: {}", "[WARNING]".yellow(), e);
+        }
+    }
+}
+
+/// Serves a recording's responses in place of the network.
+///
+/// Each URL gets its recorded responses in order, each held back until the
+/// replay clock reaches the time it was received. A URL polled more often
+/// than it was recorded keeps getting its last response.
+pub struct Player {
+    responses: std::sync::Mutex<HashMap<String, VecDeque<RecordedResponse>>>,
+    // The response each URL was last served
+    served: std::sync::Mutex<HashMap<String, RecordedResponse>>,
+    first: DateTime<Utc>,
+    last: DateTime<Utc>,
+    started: Instant,
+    speed: f64,
+}
+
+impl Player {
+    /// Load a recording to be played back `speed` times faster than it was made.
+    pub fn load(dir: &Path, speed: f64) -> Result<Self, RecordingError> {
+        let path = dir.join(RESPONSES_FILE);
+        let file =
+            File::open(&path).map_err(|e| RecordingError::Io(path.display().to_string(), e))?;
+
+        let mut responses: HashMap<String, VecDeque<RecordedResponse>> = HashMap::new();
+        let mut first: Option<DateTime<Utc>> = None;
+        let mut last: Option<DateTime<Utc>> = None;
+
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| RecordingError::Io(path.display().to_string(), e))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response: RecordedResponse = serde_json::from_str(&line).map_err(|e| {
+                RecordingError::Invalid(
+                    dir.display().to_string(),
+                    format!("line {}: {}", number + 1, e),
+                )
+            })?;
+
+            first = Some(first.map_or(response.at, |t| t.min(response.at)));
+            last = Some(last.map_or(response.at, |t| t.max(response.at)));
+            responses
+                .entry(response.url.clone())
+                .or_default()
+                .push_back(response);
+        }
+
+        let (Some(first), Some(last)) = (first, last) else {
+            return Err(RecordingError::Invalid(
+                dir.display().to_string(),
+                "no responses were recorded".to_string(),
+            ));
+        };
+
+        Ok(Self {
+            responses: std::sync::Mutex::new(responses),
+            served: std::sync::Mutex::new(HashMap::new()),
+            first,
+            last,
+            started: Instant::now(),
+            speed,
+        })
+    }
+
+    /// The next recorded response for `url`, once it is due. Once its
+    /// responses run out, the last one is served again, as a poll that finds
+    /// nothing new. `None` when the recording has no responses for it at all.
+    pub async fn next(&self, url: &str) -> Option<RecordedResponse> {
+        let response = {
+            let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+            responses.get_mut(url).and_then(VecDeque::pop_front)
+        };
+
+        let Some(response) = response else {
+            let last = {
+                let served = self.served.lock().unwrap_or_else(|e| e.into_inner());
+                served.get(url).cloned()
+            };
+            if last.is_some() {
+                return last;
+            }
+
+            // Past the end of the recording, requests wait for the replay to stop
+            if Instant::now() >= self.due(self.last) {
+                std::future::pending::<()>().await;
+            }
+            return None;
+        };
+
+        tokio::time::sleep_until(self.due(response.at).into()).await;
+        self.served
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(url.to_string(), response.clone());
+        Some(response)
+    }
+
+    /// Resolves once every response has come due and the pipeline has had
+    /// time to handle the last one.
+    pub async fn finished(&self) {
+        tokio::time::sleep_until((self.due(self.last) + REPLAY_DRAIN).into()).await;
+    }
+
+    /// Responses that were never asked for, e.g. because the replay took a
+    /// different path than the recorded session.
+    pub fn unused(&self) -> usize {
+        let responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        responses.values().map(VecDeque::len).sum()
+    }
+
+    fn due(&self, at: DateTime<Utc>) -> Instant {
+        let offset = (at - self.first).to_std().unwrap_or_default();
+        self.started + offset.div_f64(self.speed)
+    }
+}
+
+/// Parse a replay speed such as `10x` or `0.5`.
+pub fn parse_speed(s: &str) -> Result<f64, String> {
+    let speed: f64 = s
+        .trim_end_matches(['x', 'X'])
+        .parse()
+        .map_err(|_| format!("expected a speed such as 10x, got '{}'", s))?;
+    if speed.is_finite() && speed > 0.0 {
+        Ok(speed)
+    } else {
+        Err("the speed must be greater than zero".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::http::{HttpClient, HttpSettings, Tap};
+    use crate::manifold;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    const BETS_URL: &str = "https://api.manifold.markets/v0/bets?limit=100";
+
+    fn fixture() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/recording")
+    }
+
+    #[test]
+    fn loads_the_session() {
+        let session = Session::load(&fixture()).unwrap();
+        assert_eq!(session.args[0], "watch");
+        assert_eq!(
+            session.endpoints.manifold_api,
+            "https://api.manifold.markets/v0"
+        );
+    }
+
+    #[tokio::test]
+    async fn exhausted_url_repeats_its_last_response() {
+        let player = Player::load(&fixture(), 1000.0).unwrap();
+
+        let first = player.next(BETS_URL).await.unwrap();
+        let second = player.next(BETS_URL).await.unwrap();
+        assert_ne!(first.body, second.body);
+        assert_eq!(player.unused(), 0);
+
+        // More polls than were recorded get the last response, not a 404
+        let repeat = player.next(BETS_URL).await.unwrap();
+        assert_eq!(repeat.status, 200);
+        assert_eq!(repeat.body, second.body);
+    }
+
+    #[tokio::test]
+    async fn unrecorded_url_is_not_found() {
+        // Played in real time, so the recording hasn't ended yet
+        let player = Player::load(&fixture(), 1.0).unwrap();
+        let response = player
+            .next("https://api.manifold.markets/v0/market/x")
+            .await;
+        assert!(response.is_none());
+    }
+
+    #[tokio::test]
+    async fn replayed_polls_keep_succeeding() {
+        let player = Arc::new(Player::load(&fixture(), 1000.0).unwrap());
+        let http = HttpClient::new(HttpSettings::from_config(&Config::default()))
+            .with_tap(Tap::Replay(player));
+        let api_url = "https://api.manifold.markets/v0";
+
+        let mut polls = Vec::new();
+        for _ in 0..4 {
+            let batch = manifold::fetch_bets_until(&http, api_url, 1, |_| false)
+                .await
+                .expect("replayed poll failed");
+            let ids: Vec<String> = batch.trades.into_iter().map(|b| b.id).collect();
+            polls.push(ids);
+        }
+
+        assert_eq!(polls[0], ["bet2", "bet1"]);
+        assert_eq!(polls[1], ["bet3", "bet2", "bet1"]);
+        assert_eq!(polls[2], polls[1]);
+        assert_eq!(polls[3], polls[1]);
+    }
+}
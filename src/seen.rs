@@ -0,0 +1,275 @@
+// Bounded, time-limited record of trades that have already been processed
+//
+// Replaces comparing against a single "last trade ID": the feeds can reorder
+// trades or drop one from a page, and a single Polymarket transaction can
+// settle several fills.
+
+use serde::Deserialize;
+use std::collections::{HashMap, VecDeque};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_TTL_SECS: u64 = 24 * 3600;
+pub const DEFAULT_CAPACITY: usize = 100_000;
+
+// The file was a single JSON object before entries were appended
+#[derive(Deserialize)]
+struct SeenFile {
+    entries: Vec<(String, u64)>,
+}
+
+pub struct SeenTrades {
+    ttl_secs: u64,
+    capacity: usize,
+    // Key -> time first seen (unix seconds)
+    entries: HashMap<String, u64>,
+    // Insertion order, oldest first, for eviction
+    order: VecDeque<(String, u64)>,
+    path: Option<PathBuf>,
+    // Entries at the back of `order` not yet written
+    unsaved: usize,
+    // Lines in the file, expired ones included; `None` until the file has
+    // been written as lines
+    written: Option<usize>,
+}
+
+impl SeenTrades {
+    pub fn new(ttl_secs: u64, capacity: usize) -> Self {
+        Self {
+            ttl_secs,
+            capacity,
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            path: None,
+            unsaved: 0,
+            written: None,
+        }
+    }
+
+    /// A set that is loaded from and saved to `path`.
+    ///
+    /// A missing or unreadable file starts an empty set.
+    pub fn persistent(path: PathBuf, ttl_secs: u64, capacity: usize) -> Self {
+        let mut seen = Self::new(ttl_secs, capacity);
+
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            let entries = match serde_json::from_str::<SeenFile>(&text) {
+                Ok(file) => file.entries,
+                Err(_) => {
+                    // A line cut short by a crash is skipped, and the file
+                    // rewritten rather than appended to
+                    if text.is_empty() || text.ends_with('\n') {
+                        seen.written = Some(text.lines().count());
+                    }
+                    text.lines()
+                        .filter_map(|line| serde_json::from_str(line).ok())
+                        .collect()
+                }
+            };
+            for (key, ts) in entries {
+                seen.entries.insert(key.clone(), ts);
+                seen.order.push_back((key, ts));
+            }
+            seen.evict(now());
+        }
+
+        seen.path = Some(path);
+        seen
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        match self.entries.get(key) {
+            Some(ts) => now().saturating_sub(*ts) < self.ttl_secs,
+            None => false,
+        }
+    }
+
+    /// Record a key. Returns `true` if it hadn't been seen before.
+    pub fn insert(&mut self, key: &str) -> bool {
+        if self.contains(key) {
+            return false;
+        }
+
+        let ts = now();
+        self.entries.insert(key.to_string(), ts);
+        self.order.push_back((key.to_string(), ts));
+        self.unsaved += 1;
+        self.evict(ts);
+        true
+    }
+
+    /// Write the keys added since the last save to disk, if the set is
+    /// persistent.
+    ///
+    /// New keys are appended. Once expired keys make up most of the file, it
+    /// is rewritten with just the live ones.
+    pub fn save(&mut self) -> std::io::Result<()> {
+        let Some(path) = self.path.as_deref() else {
+            return Ok(());
+        };
+        if self.unsaved == 0 {
+            return Ok(());
+        }
+
+        let new = self.unsaved.min(self.order.len());
+        match self.written {
+            Some(written) if written + new <= 2 * self.order.len() => {
+                append_lines(path, self.order.range(self.order.len() - new..))?;
+                self.written = Some(written + new);
+            }
+            _ => {
+                write_file(path, &self.order)?;
+                self.written = Some(self.order.len());
+            }
+        }
+        self.unsaved = 0;
+        Ok(())
+    }
+
+    fn evict(&mut self, now: u64) {
+        while let Some((key, ts)) = self.order.front() {
+            let expired = now.saturating_sub(*ts) >= self.ttl_secs;
+            if !expired && self.order.len() <= self.capacity {
+                break;
+            }
+
+            // Only drop the map entry if it wasn't re-inserted later
+            if self.entries.get(key) == Some(ts) {
+                self.entries.remove(key);
+            }
+            self.order.pop_front();
+        }
+    }
+}
+
+// One JSON `[key, time]` pair per line
+fn to_lines<'a, I>(entries: I) -> std::io::Result<String>
+where
+    I: Iterator<Item = &'a (String, u64)>,
+{
+    let mut lines = String::new();
+    for entry in entries {
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    Ok(lines)
+}
+
+fn append_lines<'a, I>(path: &Path, entries: I) -> std::io::Result<()>
+where
+    I: Iterator<Item = &'a (String, u64)>,
+{
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(to_lines(entries)?.as_bytes())
+}
+
+fn write_file(path: &Path, order: &VecDeque<(String, u64)>) -> std::io::Result<()> {
+    // Write then rename so a crash mid-write doesn't lose the whole set
+    let tmp = path.with_extension("json.tmp");
+    std::fs::write(&tmp, to_lines(order.iter())?)?;
+    std::fs::rename(tmp, path)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A file in the temp dir, removed when dropped
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let file_name = format!("wwatcher-{}-{}.json", name, std::process::id());
+            Self(std::env::temp_dir().join(file_name))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn lines(path: &Path) -> usize {
+        std::fs::read_to_string(path).unwrap().lines().count()
+    }
+
+    #[test]
+    fn new_keys_are_appended() {
+        let file = TempFile::new("seen-append");
+        let mut seen = SeenTrades::persistent(file.0.clone(), 3600, 100);
+        seen.insert("a");
+        seen.insert("b");
+        seen.save().unwrap();
+        seen.insert("c");
+        seen.save().unwrap();
+        // Nothing new, nothing written
+        seen.save().unwrap();
+        assert_eq!(lines(&file.0), 3);
+
+        let reloaded = SeenTrades::persistent(file.0.clone(), 3600, 100);
+        assert_eq!(reloaded.len(), 3);
+        assert!(reloaded.contains("a") && reloaded.contains("c"));
+    }
+
+    #[test]
+    fn evicted_keys_are_compacted_away() {
+        let file = TempFile::new("seen-compact");
+        let mut seen = SeenTrades::persistent(file.0.clone(), 3600, 2);
+        for key in ["a", "b", "c", "d", "e"] {
+            seen.insert(key);
+            seen.save().unwrap();
+        }
+        // Never more than twice the live keys
+        assert!(lines(&file.0) <= 4);
+
+        let reloaded = SeenTrades::persistent(file.0.clone(), 3600, 2);
+        assert_eq!(reloaded.len(), 2);
+        assert!(reloaded.contains("d") && reloaded.contains("e"));
+    }
+
+    #[test]
+    fn reads_the_old_format() {
+        let file = TempFile::new("seen-legacy");
+        let ts = now();
+        let legacy = serde_json::json!({ "entries": [["a", ts], ["b", ts]] });
+        std::fs::write(&file.0, legacy.to_string()).unwrap();
+
+        let mut seen = SeenTrades::persistent(file.0.clone(), 3600, 100);
+        assert!(seen.contains("a") && seen.contains("b"));
+
+        // The first save rewrites it as lines
+        seen.insert("c");
+        seen.save().unwrap();
+        assert_eq!(lines(&file.0), 3);
+        assert_eq!(SeenTrades::persistent(file.0.clone(), 3600, 100).len(), 3);
+    }
+
+    #[test]
+    fn torn_last_line_is_skipped() {
+        let file = TempFile::new("seen-torn");
+        let ts = now();
+        std::fs::write(&file.0, format!("[\"a\",{}]\n[\"b\",{}]\n[\"c", ts, ts)).unwrap();
+
+        let mut seen = SeenTrades::persistent(file.0.clone(), 3600, 100);
+        assert_eq!(seen.len(), 2);
+
+        seen.insert("d");
+        seen.save().unwrap();
+        let reloaded = SeenTrades::persistent(file.0.clone(), 3600, 100);
+        assert!(reloaded.contains("d"));
+    }
+}
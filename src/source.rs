@@ -0,0 +1,81 @@
+// Trade sources
+//
+// Each platform implements `TradeSource`, converting its trades to
+// `NormalizedTrade`. The watcher runs the same pipeline over every source,
+// so a new venue only needs an adapter.
+
+use crate::kalshi::KalshiError;
+use crate::manifold::ManifoldError;
+use crate::polymarket::PolymarketError;
+use crate::types::{NormalizedTrade, Platform, StreamEvent, TradeBatch};
+use std::future::Future;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+#[derive(Error, Debug)]
+pub enum SourceError {
+    #[error(transparent)]
+    Polymarket(#[from] PolymarketError),
+    #[error(transparent)]
+    Kalshi(#[from] KalshiError),
+    #[error(transparent)]
+    Manifold(#[from] ManifoldError),
+}
+
+pub trait TradeSource: Send + 'static {
+    fn platform(&self) -> Platform;
+
+    /// Fetch recent trades, newest first, paging back until a trade older
+    /// than `stop_before` (unix seconds) or `max_pages` is reached.
+    fn fetch_trades(
+        &mut self,
+        max_pages: usize,
+        stop_before: Option<i64>,
+    ) -> impl Future<Output = Result<TradeBatch<NormalizedTrade>, SourceError>> + Send;
+
+    /// Fetch the trades made between `since` and `until` (unix seconds),
+    /// newest first, for backfills. Sources whose API can't start from a
+    /// point in time page back from the newest trade, so `max_pages` limits
+    /// how far back they reach.
+    fn fetch_range(
+        &mut self,
+        since: i64,
+        until: i64,
+        max_pages: usize,
+    ) -> impl Future<Output = Result<TradeBatch<NormalizedTrade>, SourceError>> + Send {
+        async move {
+            let mut batch = self.fetch_trades(max_pages, Some(since)).await?;
+            batch.trades.retain(|t| t.timestamp.timestamp() <= until);
+            Ok(batch)
+        }
+    }
+
+    /// Start the live trade stream, if this source was set up with one.
+    fn take_stream(&mut self) -> Option<mpsc::Receiver<StreamEvent<NormalizedTrade>>> {
+        None
+    }
+
+    /// Whether polls carry on while the stream is connected, because the
+    /// stream covers only part of what the polls do.
+    fn polls_while_streaming(&self) -> bool {
+        false
+    }
+
+    /// Fill in market details for trades that are about to be alerted on.
+    fn enrich(&mut self, _trades: &mut [NormalizedTrade]) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+
+    /// Write any cached state to disk.
+    fn save(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Whether a trade at `ts` is past the point a poll should stop at.
+pub fn is_older(ts: Option<i64>, stop_before: Option<i64>) -> bool {
+    match (ts, stop_before) {
+        (Some(ts), Some(stop_before)) => ts < stop_before,
+        _ => false,
+    }
+}
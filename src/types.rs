@@ -1,78 +1,632 @@
 // Shared types and utilities across modules
 
+use crate::actors::{ActorLevel, ActorWindow};
+use crate::positions::{self, Holding, PositionBook};
+use crate::wallet_store::{WalletStore, WalletStoreError};
+use colored::Colorize;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::Path;
+use std::time::Duration;
+
+/// A prediction market platform the watcher can follow.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+pub enum Platform {
+    Polymarket,
+    Kalshi,
+    Manifold,
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Platform::Polymarket => write!(f, "Polymarket"),
+            Platform::Kalshi => write!(f, "Kalshi"),
+            Platform::Manifold => write!(f, "Manifold"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Side {
+    Buy,
+    Sell,
+}
+
+impl std::fmt::Display for Side {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Side::Buy => write!(f, "BUY"),
+            Side::Sell => write!(f, "SELL"),
+        }
+    }
+}
+
+/// What a wallet did. Splits, merges and redeems only come from following a
+/// wallet's own activity; every feed trade is a `Trade`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum TradeKind {
+    #[default]
+    Trade,
+    /// Dollars turned into one share of every outcome each
+    Split,
+    /// One share of every outcome each turned back into dollars
+    Merge,
+    /// Shares of a resolved market cashed in
+    Redeem,
+}
+
+impl std::fmt::Display for TradeKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TradeKind::Trade => write!(f, "TRADE"),
+            TradeKind::Split => write!(f, "SPLIT"),
+            TradeKind::Merge => write!(f, "MERGE"),
+            TradeKind::Redeem => write!(f, "REDEEM"),
+        }
+    }
+}
+
+/// A trade from any platform, in common units.
+///
+/// Each platform adapter converts its own trades into this shape; everything
+/// after that (thresholds, wallet tracking, anomalies, alerts) works on it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NormalizedTrade {
+    pub platform: Platform,
+    /// Unique per fill within the platform
+    pub trade_id: String,
+    /// Condition ID, ticker or the platform's equivalent
+    pub market_id: String,
+    pub market_title: Option<String>,
+    /// The outcome token or contract side that changed hands
+    pub outcome_id: String,
+    pub outcome: Option<String>,
+    pub side: Side,
+    #[serde(default)]
+    pub kind: TradeKind,
+    pub contracts: Decimal,
+    /// Price per contract in dollars (0.0-1.0)
+    pub price: Decimal,
+    /// What the taker paid (or received, on a sell) in dollars, before fees
+    pub notional: Decimal,
+    /// What the taker paid including fees, where the platform's fee is known
+    pub cost: Option<Decimal>,
+    /// What the contracts pay out if their outcome wins, for buys
+    pub max_payout: Option<Decimal>,
+    /// The trade as a change in exposure, where the market is binary and the
+    /// traded outcome's place in it is known
+    pub exposure: Option<Exposure>,
+    /// Wallet or account, where the platform exposes one
+    pub actor_id: Option<String>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Market details looked up before alerting
+    pub market: Option<MarketInfo>,
+    /// The trade as the platform sent it
+    pub raw: serde_json::Value,
+}
+
+impl NormalizedTrade {
+    /// Key that identifies the same trade whichever feed it came from.
+    /// Streams don't always carry the IDs the REST endpoints use.
+    pub fn content_key(&self) -> String {
+        format!(
+            "{}:{}:{}:{:.6}:{:.6}",
+            self.market_id,
+            self.outcome_id,
+            self.timestamp.timestamp(),
+            self.contracts,
+            self.price
+        )
+    }
+}
+
+/// A trade restated as a change in exposure to a binary market's first
+/// outcome.
+///
+/// Buying one outcome is the same bet as selling the other, so buying No at
+/// 0.10 and selling Yes at 0.90 both come out as shedding Yes at 0.90.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Exposure {
+    /// Name of the first outcome, once known
+    pub outcome: Option<String>,
+    /// Contracts of the first outcome gained, or shed when negative
+    pub contracts: Decimal,
+    /// Implied probability of the first outcome at the trade's price
+    pub probability: Decimal,
+}
+
+impl Exposure {
+    /// Exposure of a trade in outcome `index` (0 or 1) of a binary market.
+    pub fn binary(index: usize, side: Side, contracts: Decimal, price: Decimal) -> Option<Self> {
+        let (contracts, probability) = match index {
+            0 => (contracts, price),
+            1 => (-contracts, Decimal::ONE - price),
+            _ => return None,
+        };
+
+        Some(Self {
+            outcome: None,
+            contracts: match side {
+                Side::Buy => contracts,
+                Side::Sell => -contracts,
+            },
+            probability,
+        })
+    }
+}
+
+/// A trader's net exposure in one market just before and after a trade.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct PositionChange {
+    pub before: Decimal,
+    pub after: Decimal,
+}
+
+impl PositionChange {
+    /// Whether the trade cut back a position built earlier. Trading out of
+    /// one side and into the other counts.
+    pub fn is_exit(&self) -> bool {
+        !self.before.is_zero()
+            && self.before.is_sign_positive() != (self.after - self.before).is_sign_positive()
+    }
+}
+
+/// Market details used to put an alert in context.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketInfo {
+    pub end_date: Option<String>,
+    pub category: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub liquidity: Option<f64>,
+    pub volume_24h: Option<f64>,
+    /// The event the market belongs to (slug or ticker)
+    pub event: Option<String>,
+    pub url: Option<String>,
+    /// Outcome names in order, where the platform lists them
+    #[serde(default)]
+    pub outcomes: Vec<String>,
+}
+
+impl MarketInfo {
+    pub fn end_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        chrono::DateTime::parse_from_rfc3339(self.end_date.as_deref()?)
+            .ok()
+            .map(|dt| dt.with_timezone(&chrono::Utc))
+    }
+}
+
+/// Events emitted by the platform WebSocket streams.
+#[derive(Debug)]
+pub enum StreamEvent<T> {
+    Connected,
+    Disconnected(String),
+    Trade(T),
+}
+
+/// Trades fetched since the last poll, newest first.
+#[derive(Debug)]
+pub struct TradeBatch<T> {
+    pub trades: Vec<T>,
+    /// Number of pages requested
+    pub pages: usize,
+    /// The last seen trade wasn't reached within the page limit, so older
+    /// trades may have been missed
+    pub coverage_gap: bool,
+    /// Items the API returned across all pages, including ones already seen
+    /// and ones that couldn't be parsed
+    pub received: usize,
+    /// Items skipped because they didn't match the expected format
+    pub dropped: usize,
+    /// One of the skipped items, for diagnosing format changes
+    pub drop_sample: Option<String>,
+}
+
+impl<T> TradeBatch<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> TradeBatch<U> {
+        TradeBatch {
+            trades: self.trades.into_iter().map(f).collect(),
+            pages: self.pages,
+            coverage_gap: self.coverage_gap,
+            received: self.received,
+            dropped: self.dropped,
+            drop_sample: self.drop_sample,
+        }
+    }
+
+    pub fn add_page<U>(&mut self, page: &ParsedPage<U>) {
+        self.pages += 1;
+        self.received += page.received;
+        self.dropped += page.dropped;
+        if self.drop_sample.is_none() {
+            self.drop_sample = page.drop_sample.clone();
+        }
+    }
+
+    /// Fold in a batch fetched from another query of the same feed.
+    pub fn merge(&mut self, other: TradeBatch<T>) {
+        self.trades.extend(other.trades);
+        self.pages += other.pages;
+        self.coverage_gap |= other.coverage_gap;
+        self.received += other.received;
+        self.dropped += other.dropped;
+        if self.drop_sample.is_none() {
+            self.drop_sample = other.drop_sample;
+        }
+    }
+
+    /// Items that parsed successfully.
+    pub fn parsed(&self) -> usize {
+        self.received - self.dropped
+    }
+}
+
+impl<T> Default for TradeBatch<T> {
+    fn default() -> Self {
+        Self {
+            trades: Vec::new(),
+            pages: 0,
+            coverage_gap: false,
+            received: 0,
+            dropped: 0,
+            drop_sample: None,
+        }
+    }
+}
+
+/// The items of one API page that could be converted, plus a count of the
+/// ones that couldn't.
+#[derive(Debug)]
+pub struct ParsedPage<T> {
+    pub items: Vec<T>,
+    pub received: usize,
+    pub dropped: usize,
+    pub drop_sample: Option<String>,
+}
+
+/// Convert each raw item, counting (rather than silently skipping) the
+/// ones `convert` rejects.
+pub fn parse_items<T, F>(raw: &[serde_json::Value], convert: F) -> ParsedPage<T>
+where
+    F: Fn(&serde_json::Value) -> Option<T>,
+{
+    let mut page = ParsedPage {
+        items: Vec::with_capacity(raw.len()),
+        received: raw.len(),
+        dropped: 0,
+        drop_sample: None,
+    };
+
+    for item in raw {
+        match convert(item) {
+            Some(converted) => page.items.push(converted),
+            None => {
+                page.dropped += 1;
+                page.drop_sample
+                    .get_or_insert_with(|| payload_sample(&item.to_string()));
+            }
+        }
+    }
+
+    page
+}
+
+const PAYLOAD_SAMPLE_CHARS: usize = 300;
+
+/// The start of a response body, for error messages.
+pub fn payload_sample(text: &str) -> String {
+    let text = text.trim();
+    match text.char_indices().nth(PAYLOAD_SAMPLE_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
+
+/// Change in a platform feed's health, reported alongside trade alerts.
+#[derive(Debug, Clone)]
+pub enum FeedEvent {
+    Degraded {
+        platform: Platform,
+        polls: u32,
+        reason: String,
+        escalated: bool,
+    },
+    Recovered {
+        platform: Platform,
+        polls: u32,
+    },
+}
+
+pub const DEFAULT_FEED_DEGRADED_AFTER: u32 = 5;
+
+/// Counts consecutive empty or failed polls of one feed.
+///
+/// Reports the feed as degraded after `threshold` bad polls in a row, again
+/// (escalated) each time the streak doubles, and once more when it recovers.
+pub struct FeedHealth {
+    platform: Platform,
+    threshold: u32,
+    consecutive: u32,
+    next_alert: u32,
+}
+
+impl FeedHealth {
+    pub fn new(platform: Platform, threshold: u32) -> Self {
+        let threshold = threshold.max(1);
+        Self {
+            platform,
+            threshold,
+            consecutive: 0,
+            next_alert: threshold,
+        }
+    }
+
+    pub fn record_ok(&mut self) -> Option<FeedEvent> {
+        let polls = std::mem::take(&mut self.consecutive);
+        let was_degraded = self.next_alert > self.threshold;
+        self.next_alert = self.threshold;
+
+        was_degraded.then_some(FeedEvent::Recovered {
+            platform: self.platform,
+            polls,
+        })
+    }
+
+    pub fn record_problem(&mut self, reason: String) -> Option<FeedEvent> {
+        self.consecutive += 1;
+        if self.consecutive < self.next_alert {
+            return None;
+        }
+
+        let escalated = self.next_alert > self.threshold;
+        self.next_alert = self.next_alert.saturating_mul(2);
+        Some(FeedEvent::Degraded {
+            platform: self.platform,
+            polls: self.consecutive,
+            reason,
+            escalated,
+        })
+    }
+}
+
+/// Reconnect delays for the WebSocket streams.
+pub const STREAM_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+pub const STREAM_MAX_BACKOFF: Duration = Duration::from_secs(60);
 
 pub struct WalletTracker {
-    // Map of wallet/account ID to list of transaction values and timestamps
-    transactions: HashMap<String, Vec<(f64, u64)>>,
+    // Map of wallet/account ID to list of transaction values and trade times
+    transactions: HashMap<String, Vec<(Decimal, i64)>>,
+    // Net exposure per wallet and market, with the time of the last trade
+    positions: HashMap<String, HashMap<String, (Decimal, i64)>>,
+    // What each wallet holds of each outcome, and what it has made
+    book: PositionBook,
+    // Newest trade time seen, which old transactions are aged against
+    latest: i64,
+    // When stale positions were last cleared out
+    positions_swept: i64,
+    // Where transactions, exposure and holdings are written through to, when
+    // persistent
+    store: Option<WalletStore>,
+    // Windows activity is measured over
+    windows: Vec<ActorWindow>,
+    // How long transactions are kept: the longest window, and at least a day
+    retention_secs: i64,
 }
 
 impl WalletTracker {
-    pub fn new() -> Self {
+    pub fn new(windows: Vec<ActorWindow>) -> Self {
+        let retention_secs = windows
+            .iter()
+            .map(|w| w.window.secs())
+            .fold(86400, i64::max);
+
         Self {
+            windows,
+            retention_secs,
             transactions: HashMap::new(),
+            positions: HashMap::new(),
+            book: PositionBook::new(),
+            latest: 0,
+            positions_swept: 0,
+            store: None,
         }
     }
 
-    pub fn record_transaction(&mut self, wallet_id: &str, value: f64) {
-        let timestamp = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+    /// A tracker backed by the SQLite database at `path`: the holdings,
+    /// exposure and the transactions still inside the longest window are
+    /// loaded now, and every change is saved as it's recorded.
+    pub fn persistent(path: &Path, windows: Vec<ActorWindow>) -> Result<Self, WalletStoreError> {
+        let mut tracker = Self::new(windows);
+        let store = WalletStore::open(path, tracker.retention_secs)?;
+
+        for (wallet_id, value, timestamp) in store.load_recent()? {
+            tracker
+                .transactions
+                .entry(wallet_id)
+                .or_default()
+                .push((value, timestamp));
+            tracker.latest = tracker.latest.max(timestamp);
+        }
+        for (wallet_id, market_id, contracts, timestamp) in store.load_exposures()? {
+            tracker
+                .positions
+                .entry(wallet_id)
+                .or_default()
+                .insert(market_id, (contracts, timestamp));
+            tracker.latest = tracker.latest.max(timestamp);
+        }
+        tracker.book = store.load_book(None)?;
+
+        tracker.store = Some(store);
+        Ok(tracker)
+    }
+
+    /// Number of transactions held.
+    pub fn len(&self) -> usize {
+        self.transactions.values().map(Vec::len).sum()
+    }
+
+    /// Add a trade's exposure (contracts of the market's first outcome) to
+    /// the wallet's position in `market_id`.
+    ///
+    /// Every trade should be recorded, not just the ones that alert, so the
+    /// position a large trade starts from is known.
+    pub fn record_exposure(
+        &mut self,
+        wallet_id: &str,
+        market_id: &str,
+        contracts: Decimal,
+        timestamp: i64,
+    ) -> PositionChange {
+        let markets = self.positions.entry(wallet_id.to_string()).or_default();
+        let (before, last_trade) = markets.remove(market_id).unwrap_or_default();
+        let after = before + contracts;
+        let last_trade = last_trade.max(timestamp);
+        if !after.is_zero() {
+            markets.insert(market_id.to_string(), (after, last_trade));
+        }
+
+        if let Some(store) = self.store.as_mut() {
+            if let Err(e) = store.save_exposure(wallet_id, market_id, after, last_trade) {
+                eprintln!(
+                    "{} Could not save wallet exposure: {}",
+                    "[WARNING]".yellow(),
+                    e
+                );
+            }
+        }
+
+        self.advance(timestamp);
+
+        PositionChange { before, after }
+    }
+
+    /// Book a trade against its trader's holding in the outcome, and mark
+    /// the outcome to the trade's price. Returns the holding after the trade.
+    ///
+    /// Like exposure, every trade should be recorded.
+    pub fn record_holding(&mut self, trade: &NormalizedTrade) -> Option<Holding> {
+        let holding = self.book.record(trade);
+        let timestamp = trade.timestamp.timestamp();
+
+        if let (Some(store), Some(wallet_id), Some(holding)) = (
+            self.store.as_mut(),
+            trade.actor_id.as_deref(),
+            holding.as_ref(),
+        ) {
+            if let Err(e) = store.save_holding(wallet_id, holding, timestamp) {
+                eprintln!(
+                    "{} Could not save wallet holding: {}",
+                    "[WARNING]".yellow(),
+                    e
+                );
+            }
+        }
 
+        self.advance(timestamp);
+        holding
+    }
+
+    // Move trade time forward, clearing out stale positions hourly
+    fn advance(&mut self, timestamp: i64) {
+        self.latest = self.latest.max(timestamp);
+        if self.latest - self.positions_swept >= 3600 {
+            self.cleanup_old_positions();
+        }
+    }
+
+    fn cleanup_old_positions(&mut self) {
+        let latest = self.latest;
+
+        for markets in self.positions.values_mut() {
+            markets.retain(|_, (_, ts)| latest - *ts < positions::RETENTION_SECS);
+        }
+        self.positions.retain(|_, markets| !markets.is_empty());
+        self.book.sweep(latest);
+        self.positions_swept = latest;
+    }
+
+    /// Record a transaction made at `timestamp` (unix seconds). Windows are
+    /// measured in trade time, so replaying old trades gives the activity
+    /// the wallet had back then.
+    pub fn record_transaction(&mut self, wallet_id: &str, value: Decimal, timestamp: i64) {
         self.transactions
             .entry(wallet_id.to_string())
             .or_default()
             .push((value, timestamp));
 
-        // Keep only last 24 hours of data
+        if let Some(store) = self.store.as_mut() {
+            if let Err(e) = store.insert(wallet_id, value, timestamp) {
+                eprintln!(
+                    "{} Could not save wallet activity: {}",
+                    "[WARNING]".yellow(),
+                    e
+                );
+            }
+        }
+
+        // Keep only what the longest window can still see
+        self.latest = self.latest.max(timestamp);
         self.cleanup_old_transactions();
     }
 
-    pub fn get_activity(&self, wallet_id: &str) -> WalletActivity {
-        if let Some(txns) = self.transactions.get(wallet_id) {
-            let current_time = SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap()
-                .as_secs();
+    /// Activity in the hour, the day and each configured window up to `at`
+    /// (unix seconds).
+    pub fn get_activity(&self, wallet_id: &str, at: i64) -> WalletActivity {
+        let txns = self
+            .transactions
+            .get(wallet_id)
+            .map_or(&[][..], Vec::as_slice);
+        // Count and volume of the transactions less than `secs` old
+        let within = |secs: i64| {
+            txns.iter()
+                .filter(|(_, ts)| *ts <= at && at - ts < secs)
+                .fold((0, Decimal::ZERO), |(count, total), (value, _)| {
+                    (count + 1, total + value)
+                })
+        };
 
-            // Filter to last hour
-            let hour_txns: Vec<&(f64, u64)> = txns
-                .iter()
-                .filter(|(_, ts)| current_time - ts < 3600)
-                .collect();
+        let (transactions_last_hour, total_value_hour) = within(3600);
+        let (transactions_last_day, total_value_day) = within(86400);
+        let windows: Vec<WindowActivity> = self
+            .windows
+            .iter()
+            .map(|window| {
+                let (transactions, volume) = within(window.window.secs());
+                WindowActivity {
+                    window: window.clone(),
+                    transactions,
+                    volume,
+                }
+            })
+            .collect();
 
-            // Filter to last 24 hours
-            let day_txns: Vec<&(f64, u64)> = txns
+        let flagged = |level| {
+            windows
                 .iter()
-                .filter(|(_, ts)| current_time - ts < 86400)
-                .collect();
-
-            let total_value_hour: f64 = hour_txns.iter().map(|(v, _)| v).sum();
-            let total_value_day: f64 = day_txns.iter().map(|(v, _)| v).sum();
-
-            WalletActivity {
-                transactions_last_hour: hour_txns.len(),
-                transactions_last_day: day_txns.len(),
-                total_value_hour,
-                total_value_day,
-                is_repeat_actor: hour_txns.len() > 1,
-                is_heavy_actor: day_txns.len() >= 5,
-            }
-        } else {
-            WalletActivity::default()
+                .any(|w| w.window.level == level && w.reached_transactions())
+        };
+
+        WalletActivity {
+            transactions_last_hour,
+            transactions_last_day,
+            total_value_hour,
+            total_value_day,
+            is_repeat_actor: flagged(ActorLevel::Repeat),
+            is_heavy_actor: flagged(ActorLevel::Heavy),
+            windows,
         }
     }
 
     fn cleanup_old_transactions(&mut self) {
-        let current_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        let (latest, retention_secs) = (self.latest, self.retention_secs);
 
         for txns in self.transactions.values_mut() {
-            txns.retain(|(_, ts)| current_time - ts < 86400);
+            txns.retain(|(_, ts)| latest - ts < retention_secs);
         }
 
         // Remove wallets with no recent activity
@@ -84,10 +638,38 @@ impl WalletTracker {
 pub struct WalletActivity {
     pub transactions_last_hour: usize,
     pub transactions_last_day: usize,
-    pub total_value_hour: f64,
-    pub total_value_day: f64,
+    pub total_value_hour: Decimal,
+    pub total_value_day: Decimal,
+    /// A repeat window's transaction count was reached
     pub is_repeat_actor: bool,
+    /// A heavy window's transaction count was reached
     pub is_heavy_actor: bool,
+    /// Activity in each configured window
+    pub windows: Vec<WindowActivity>,
+}
+
+/// A wallet's transactions within one actor window.
+#[derive(Debug, Clone)]
+pub struct WindowActivity {
+    pub window: ActorWindow,
+    pub transactions: usize,
+    pub volume: Decimal,
+}
+
+impl WindowActivity {
+    /// Enough transactions to flag the wallet.
+    pub fn reached_transactions(&self) -> bool {
+        self.window
+            .transactions
+            .is_some_and(|count| self.transactions >= count)
+    }
+
+    /// Enough volume to count as coordinated activity.
+    pub fn reached_volume(&self) -> bool {
+        self.window
+            .volume
+            .is_some_and(|volume| self.volume >= volume)
+    }
 }
 
 impl Default for WalletActivity {
@@ -95,10 +677,80 @@ impl Default for WalletActivity {
         Self {
             transactions_last_hour: 0,
             transactions_last_day: 0,
-            total_value_hour: 0.0,
-            total_value_day: 0.0,
+            total_value_hour: Decimal::ZERO,
+            total_value_day: Decimal::ZERO,
             is_repeat_actor: false,
             is_heavy_actor: false,
+            windows: Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn exposure_survives_a_restart() {
+        let path =
+            std::env::temp_dir().join(format!("wwatcher-exposure-{}.db", std::process::id()));
+
+        let mut tracker = WalletTracker::persistent(&path, Vec::new()).unwrap();
+        tracker.record_exposure("0xwhale", "m1", dec!(5000), 1_700_000_000);
+        tracker.record_exposure("0xwhale", "m2", dec!(100), 1_700_000_000);
+        tracker.record_exposure("0xwhale", "m2", dec!(-100), 1_700_000_060);
+        drop(tracker);
+
+        let mut tracker = WalletTracker::persistent(&path, Vec::new()).unwrap();
+        let sale = tracker.record_exposure("0xwhale", "m1", dec!(-4000), 1_700_000_120);
+        let reentry = tracker.record_exposure("0xwhale", "m2", dec!(-50), 1_700_000_120);
+        drop(tracker);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
         }
+
+        // The sale cuts the position built before the restart
+        assert_eq!(sale.before, dec!(5000));
+        assert!(sale.is_exit());
+        // The flat position wasn't kept
+        assert_eq!(reentry.before, dec!(0));
+        assert!(!reentry.is_exit());
+    }
+
+    #[test]
+    fn buying_no_is_selling_yes() {
+        let buy_no = Exposure::binary(1, Side::Buy, dec!(100), dec!(0.10)).unwrap();
+        let sell_yes = Exposure::binary(0, Side::Sell, dec!(100), dec!(0.90)).unwrap();
+        assert_eq!(buy_no.contracts, dec!(-100));
+        assert_eq!(buy_no.probability, dec!(0.90));
+        assert_eq!(
+            (buy_no.contracts, buy_no.probability),
+            (sell_yes.contracts, sell_yes.probability)
+        );
+
+        let sell_no = Exposure::binary(1, Side::Sell, dec!(100), dec!(0.10)).unwrap();
+        assert_eq!(
+            (sell_no.contracts, sell_no.probability),
+            (dec!(100), dec!(0.90))
+        );
+        assert!(Exposure::binary(2, Side::Buy, dec!(100), dec!(0.5)).is_none());
+    }
+
+    #[test]
+    fn exits_reduce_or_flip_a_position() {
+        let change = |before, after| PositionChange { before, after };
+
+        // Reducing, from either side
+        assert!(change(dec!(100), dec!(40)).is_exit());
+        assert!(change(dec!(-100), dec!(-40)).is_exit());
+        // Flipping to the other side
+        assert!(change(dec!(100), dec!(-50)).is_exit());
+        assert!(change(dec!(-100), dec!(50)).is_exit());
+        // Adding to a position, or opening one
+        assert!(!change(dec!(100), dec!(150)).is_exit());
+        assert!(!change(dec!(-100), dec!(-150)).is_exit());
+        assert!(!change(dec!(0), dec!(100)).is_exit());
+        assert!(!change(dec!(0), dec!(-100)).is_exit());
     }
 }
@@ -0,0 +1,423 @@
+// On-disk history for the wallet tracker
+//
+// SQLite tables of the transactions the tracker records and of each
+// wallet's holdings and net exposure, written as they happen and read back
+// on startup, so repeat and heavy actor detection and exit detection keep
+// their history across restarts and crashes. Other commands read the
+// holdings from here too.
+
+use crate::positions::{self, Holding, PositionBook};
+use rusqlite::{params, Connection};
+use rust_decimal::Decimal;
+use std::path::Path;
+use std::time::Duration;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum WalletStoreError {
+    #[error("Wallet database error: {0}")]
+    Database(#[from] rusqlite::Error),
+}
+
+// How often (in trade time) rows that have aged out are deleted
+const PRUNE_INTERVAL_SECS: i64 = 3600;
+
+pub struct WalletStore {
+    conn: Connection,
+    // Keep rows this close to the newest one
+    retention_secs: i64,
+    pruned_at: i64,
+}
+
+impl WalletStore {
+    /// Open (or create) the database at `path`, keeping `retention_secs` of
+    /// history.
+    pub fn open(path: &Path, retention_secs: i64) -> Result<Self, WalletStoreError> {
+        let conn = Connection::open(path)?;
+        // Another watcher may have the file open
+        conn.busy_timeout(Duration::from_secs(5))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS transactions (
+                 wallet_id TEXT NOT NULL,
+                 value TEXT NOT NULL,
+                 timestamp INTEGER NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS transactions_timestamp ON transactions (timestamp);
+             CREATE TABLE IF NOT EXISTS holdings (
+                 wallet_id TEXT NOT NULL,
+                 market_id TEXT NOT NULL,
+                 outcome_id TEXT NOT NULL,
+                 outcome TEXT,
+                 market_title TEXT,
+                 contracts TEXT NOT NULL,
+                 cost_basis TEXT NOT NULL,
+                 realized_pnl TEXT NOT NULL,
+                 payout TEXT NOT NULL,
+                 updated INTEGER NOT NULL,
+                 mark_price TEXT,
+                 PRIMARY KEY (wallet_id, market_id, outcome_id)
+             );
+             CREATE TABLE IF NOT EXISTS marks (
+                 market_id TEXT NOT NULL,
+                 outcome_id TEXT NOT NULL,
+                 price TEXT NOT NULL,
+                 timestamp INTEGER NOT NULL,
+                 PRIMARY KEY (market_id, outcome_id)
+             );
+             CREATE TABLE IF NOT EXISTS exposures (
+                 wallet_id TEXT NOT NULL,
+                 market_id TEXT NOT NULL,
+                 contracts TEXT NOT NULL,
+                 timestamp INTEGER NOT NULL,
+                 PRIMARY KEY (wallet_id, market_id)
+             );",
+        )?;
+
+        // Holdings saved before they kept their own mark
+        let has_mark: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('holdings') WHERE name = 'mark_price'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !has_mark {
+            conn.execute("ALTER TABLE holdings ADD COLUMN mark_price TEXT", [])?;
+        }
+
+        Ok(Self {
+            conn,
+            retention_secs,
+            pruned_at: 0,
+        })
+    }
+
+    /// Transactions within the retention window of the newest one, oldest
+    /// first.
+    pub fn load_recent(&self) -> Result<Vec<(String, Decimal, i64)>, WalletStoreError> {
+        let mut statement = self.conn.prepare(
+            "SELECT wallet_id, value, timestamp FROM transactions
+             WHERE timestamp > (SELECT MAX(timestamp) FROM transactions) - ?1
+             ORDER BY timestamp",
+        )?;
+        let rows = statement.query_map(params![self.retention_secs], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?;
+
+        let mut transactions = Vec::new();
+        for row in rows {
+            let (wallet_id, value, timestamp) = row?;
+            // Values are stored as decimal strings so they stay exact
+            if let Ok(value) = value.parse() {
+                transactions.push((wallet_id, value, timestamp));
+            }
+        }
+        Ok(transactions)
+    }
+
+    /// Time of the wallet's oldest saved transaction or holding. Older rows
+    /// have been pruned, so this is only as far back as the database goes.
+    pub fn first_seen(&self, wallet_id: &str) -> Result<Option<i64>, WalletStoreError> {
+        let first = self.conn.query_row(
+            "SELECT MIN(ts) FROM (
+                 SELECT timestamp AS ts FROM transactions WHERE wallet_id = ?1
+                 UNION ALL
+                 SELECT updated FROM holdings WHERE wallet_id = ?1
+             )",
+            params![wallet_id],
+            |row| row.get(0),
+        )?;
+        Ok(first)
+    }
+
+    /// Save a transaction, dropping rows that have aged out now and then.
+    pub fn insert(
+        &mut self,
+        wallet_id: &str,
+        value: Decimal,
+        timestamp: i64,
+    ) -> Result<(), WalletStoreError> {
+        self.conn.execute(
+            "INSERT INTO transactions (wallet_id, value, timestamp) VALUES (?1, ?2, ?3)",
+            params![wallet_id, value.to_string(), timestamp],
+        )?;
+
+        if timestamp - self.pruned_at >= PRUNE_INTERVAL_SECS {
+            self.conn.execute(
+                "DELETE FROM transactions WHERE timestamp <= ?1",
+                params![timestamp - self.retention_secs],
+            )?;
+            // Open holdings stay until they're sold
+            let stale = timestamp - positions::RETENTION_SECS;
+            self.conn.execute(
+                "DELETE FROM holdings WHERE updated <= ?1 AND CAST(contracts AS REAL) = 0",
+                params![stale],
+            )?;
+            self.conn
+                .execute("DELETE FROM marks WHERE timestamp <= ?1", params![stale])?;
+            self.conn.execute(
+                "DELETE FROM exposures WHERE timestamp <= ?1",
+                params![stale],
+            )?;
+            self.pruned_at = timestamp;
+        }
+        Ok(())
+    }
+
+    /// Save a wallet's net exposure to a market after a trade at `timestamp`.
+    /// A flat position is removed.
+    pub fn save_exposure(
+        &mut self,
+        wallet_id: &str,
+        market_id: &str,
+        contracts: Decimal,
+        timestamp: i64,
+    ) -> Result<(), WalletStoreError> {
+        if contracts.is_zero() {
+            self.conn.execute(
+                "DELETE FROM exposures WHERE wallet_id = ?1 AND market_id = ?2",
+                params![wallet_id, market_id],
+            )?;
+        } else {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO exposures (wallet_id, market_id, contracts, timestamp)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![wallet_id, market_id, contracts.to_string(), timestamp],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Every saved exposure: wallet, market, contracts and last trade time.
+    pub fn load_exposures(&self) -> Result<Vec<(String, String, Decimal, i64)>, WalletStoreError> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT wallet_id, market_id, contracts, timestamp FROM exposures")?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?;
+
+        let mut exposures = Vec::new();
+        for row in rows {
+            let (wallet_id, market_id, contracts, timestamp) = row?;
+            if let Ok(contracts) = contracts.parse() {
+                exposures.push((wallet_id, market_id, contracts, timestamp));
+            }
+        }
+        Ok(exposures)
+    }
+
+    /// Save a wallet's holding after a trade at `timestamp`, along with the
+    /// price it was marked to.
+    pub fn save_holding(
+        &mut self,
+        wallet_id: &str,
+        holding: &Holding,
+        timestamp: i64,
+    ) -> Result<(), WalletStoreError> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "INSERT OR REPLACE INTO holdings (wallet_id, market_id, outcome_id, outcome,
+                 market_title, contracts, cost_basis, realized_pnl, payout, updated, mark_price)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            params![
+                wallet_id,
+                holding.market_id,
+                holding.outcome_id,
+                holding.outcome,
+                holding.market_title,
+                holding.contracts.to_string(),
+                holding.cost_basis.to_string(),
+                holding.realized_pnl.to_string(),
+                holding.payout.to_string(),
+                holding.updated,
+                holding.mark_price.to_string(),
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO marks (market_id, outcome_id, price, timestamp) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT (market_id, outcome_id) DO UPDATE
+                 SET price = excluded.price, timestamp = excluded.timestamp
+                 WHERE excluded.timestamp >= marks.timestamp",
+            params![
+                holding.market_id,
+                holding.outcome_id,
+                holding.mark_price.to_string(),
+                timestamp,
+            ],
+        )?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Every saved holding and price, or just the holdings of `wallet_id`.
+    ///
+    /// Holdings whose outcome's price has been pruned keep the price they were
+    /// last saved with.
+    pub fn load_book(&self, wallet_id: Option<&str>) -> Result<PositionBook, WalletStoreError> {
+        let mut book = PositionBook::new();
+
+        let mut statement = self
+            .conn
+            .prepare("SELECT market_id, outcome_id, price, timestamp FROM marks")?;
+        let rows = statement.query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, i64>(3)?,
+            ))
+        })?;
+        for row in rows {
+            let (market_id, outcome_id, price, timestamp) = row?;
+            if let Ok(price) = price.parse() {
+                book.set_mark((market_id, outcome_id), price, timestamp);
+            }
+        }
+
+        let mut statement = self.conn.prepare(
+            "SELECT wallet_id, market_id, outcome_id, outcome, market_title, contracts,
+                 cost_basis, realized_pnl, payout, updated, mark_price
+             FROM holdings WHERE ?1 IS NULL OR wallet_id = ?1",
+        )?;
+        let rows = statement.query_map(params![wallet_id], |row| {
+            let decimal = |i: usize| -> rusqlite::Result<Option<Decimal>> {
+                // Amounts are stored as decimal strings so they stay exact
+                Ok(row.get::<_, String>(i)?.parse().ok())
+            };
+            let amounts = (decimal(5)?, decimal(6)?, decimal(7)?, decimal(8)?);
+            let (Some(contracts), Some(cost_basis), Some(realized_pnl), Some(payout)) = amounts
+            else {
+                return Ok(None);
+            };
+
+            let mut holding = Holding {
+                market_id: row.get(1)?,
+                outcome_id: row.get(2)?,
+                outcome: row.get(3)?,
+                market_title: row.get(4)?,
+                contracts,
+                cost_basis,
+                realized_pnl,
+                mark_price: Decimal::ZERO,
+                payout,
+                updated: row.get(9)?,
+            };
+            // Rows from before the column existed are marked at cost
+            holding.mark_price = match row.get::<_, Option<String>>(10)? {
+                Some(price) => price.parse().unwrap_or_default(),
+                None => holding.avg_price(),
+            };
+            Ok(Some((row.get::<_, String>(0)?, holding)))
+        })?;
+        for row in rows {
+            if let Some((wallet_id, holding)) = row? {
+                book.insert(wallet_id, holding);
+            }
+        }
+
+        Ok(book)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const T0: i64 = 1_700_000_000;
+
+    fn holding(market_id: &str, contracts: Decimal) -> Holding {
+        Holding {
+            market_id: market_id.to_string(),
+            outcome_id: format!("{}-yes", market_id),
+            outcome: Some("Yes".to_string()),
+            market_title: None,
+            contracts,
+            cost_basis: contracts * dec!(0.40),
+            realized_pnl: dec!(0),
+            mark_price: dec!(0.40),
+            payout: dec!(1),
+            updated: T0,
+        }
+    }
+
+    #[test]
+    fn prune_keeps_open_holdings() {
+        let mut store = WalletStore::open(Path::new(":memory:"), 86400).unwrap();
+        store
+            .save_holding("0xholder", &holding("m1", dec!(100)), T0)
+            .unwrap();
+        // Stored as "0.00" after selling down
+        store
+            .save_holding("0xtrader", &holding("m2", dec!(0.00)), T0)
+            .unwrap();
+
+        store
+            .insert("0xother", dec!(5), T0 + positions::RETENTION_SECS)
+            .unwrap();
+
+        let book = store.load_book(None).unwrap();
+        assert_eq!(book.holdings("0xholder").len(), 1);
+        assert!(book.holdings("0xtrader").is_empty());
+    }
+
+    #[test]
+    fn holding_keeps_its_mark_when_the_price_is_pruned() {
+        let mut store = WalletStore::open(Path::new(":memory:"), 86400).unwrap();
+        let mut held = holding("m1", dec!(100));
+        held.mark_price = dec!(0.55);
+        store.save_holding("0xholder", &held, T0).unwrap();
+
+        store
+            .insert("0xother", dec!(5), T0 + positions::RETENTION_SECS)
+            .unwrap();
+
+        let held = store.load_book(None).unwrap().holdings("0xholder");
+        assert_eq!(held[0].mark_price, dec!(0.55));
+        assert_eq!(held[0].unrealized_pnl(), dec!(15));
+    }
+
+    #[test]
+    fn old_holdings_are_marked_at_cost() {
+        let path = std::env::temp_dir().join(format!("wwatcher-test-{}.db", std::process::id()));
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE holdings (
+                 wallet_id TEXT NOT NULL,
+                 market_id TEXT NOT NULL,
+                 outcome_id TEXT NOT NULL,
+                 outcome TEXT,
+                 market_title TEXT,
+                 contracts TEXT NOT NULL,
+                 cost_basis TEXT NOT NULL,
+                 realized_pnl TEXT NOT NULL,
+                 payout TEXT NOT NULL,
+                 updated INTEGER NOT NULL,
+                 PRIMARY KEY (wallet_id, market_id, outcome_id)
+             );
+             INSERT INTO holdings VALUES
+                 ('0xholder', 'm1', 'm1-yes', 'Yes', NULL, '100', '40', '0', '1', 1700000000);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let store = WalletStore::open(&path, 86400).unwrap();
+        let held = store.load_book(None).unwrap().holdings("0xholder");
+        drop(store);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+
+        assert_eq!(held[0].mark_price, dec!(0.40));
+        assert_eq!(held[0].unrealized_pnl(), dec!(0));
+    }
+}
@@ -0,0 +1,202 @@
+// Markets and wallets followed on purpose
+//
+// Markets can be picked on the command line or kept in the config file's
+// `watchlist`. Picked markets become API query filters, and each can carry
+// its own alert threshold, typically lower than the global one so that thin
+// markets still alert.
+//
+// Wallets are kept in the config file's `wallets`, managed with `wwatcher
+// wallets`. Their trades alert whatever the size, unless the wallet is given
+// a threshold of its own.
+
+use crate::types::{NormalizedTrade, Platform};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// A watchlist entry. Exactly one of the market fields is expected.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchedMarket {
+    /// Polymarket condition ID or slug
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub market: Option<String>,
+    /// Kalshi market ticker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ticker: Option<String>,
+    /// Kalshi event ticker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub event: Option<String>,
+    /// Kalshi series ticker
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub series: Option<String>,
+    /// Alert threshold in USD for this market (default: the global threshold)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u64>,
+}
+
+impl WatchedMarket {
+    pub fn platform(&self) -> Platform {
+        if self.market.is_some() {
+            Platform::Polymarket
+        } else {
+            Platform::Kalshi
+        }
+    }
+}
+
+/// A wallet followed by address, with how to name it in alerts.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WatchedWallet {
+    /// Wallet address or trader ID
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Alert threshold in USD for this wallet's trades (default: every trade)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u64>,
+}
+
+impl WatchedWallet {
+    /// Addresses are compared ignoring case, as hex addresses come in both.
+    pub fn matches(&self, address: &str) -> bool {
+        self.address.eq_ignore_ascii_case(address)
+    }
+
+    /// The label, or the address when there's none.
+    pub fn name(&self) -> &str {
+        self.label.as_deref().unwrap_or(&self.address)
+    }
+}
+
+/// The markets selected for one Kalshi run.
+#[derive(Debug, Clone, Default)]
+pub struct KalshiScope {
+    pub tickers: Vec<String>,
+    pub events: Vec<String>,
+    pub series: Vec<String>,
+}
+
+impl KalshiScope {
+    pub fn from_watched(watched: &[WatchedMarket]) -> Self {
+        let collect = |field: fn(&WatchedMarket) -> &Option<String>| {
+            watched.iter().filter_map(|w| field(w).clone()).collect()
+        };
+
+        Self {
+            tickers: collect(|w| &w.ticker),
+            events: collect(|w| &w.event),
+            series: collect(|w| &w.series),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tickers.is_empty() && self.events.is_empty() && self.series.is_empty()
+    }
+
+    /// Whether a market is in scope. An empty scope takes every market.
+    pub fn matches(&self, ticker: &str) -> bool {
+        self.is_empty()
+            || self.tickers.iter().any(|t| t == ticker)
+            || self
+                .events
+                .iter()
+                .chain(&self.series)
+                .any(|prefix| falls_under(ticker, prefix))
+    }
+}
+
+// Kalshi tickers start with their event ticker, which starts with the series
+fn falls_under(ticker: &str, prefix: &str) -> bool {
+    ticker
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.starts_with('-'))
+}
+
+// How a threshold rule picks out trades
+#[derive(Debug, Clone)]
+enum MarketMatch {
+    Exact(String),
+    // A Kalshi event or series
+    Prefix(String),
+}
+
+impl MarketMatch {
+    fn matches(&self, market_id: &str) -> bool {
+        match self {
+            MarketMatch::Exact(id) => market_id == id,
+            MarketMatch::Prefix(prefix) => falls_under(market_id, prefix),
+        }
+    }
+}
+
+/// The alert threshold for each trade: the global one, or the lowest
+/// threshold of any watchlist entry the trade's market falls under, or of
+/// the watched wallet that made it.
+#[derive(Debug, Clone)]
+pub struct Thresholds {
+    default: Decimal,
+    rules: Vec<(MarketMatch, Decimal)>,
+    wallets: Vec<WatchedWallet>,
+}
+
+impl Thresholds {
+    pub fn new(default: u64) -> Self {
+        Self {
+            default: Decimal::from(default),
+            rules: Vec::new(),
+            wallets: Vec::new(),
+        }
+    }
+
+    /// Add the watched wallets, whose trades alert at their own threshold.
+    pub fn with_wallets(mut self, wallets: &[WatchedWallet]) -> Self {
+        self.wallets.extend(wallets.iter().cloned());
+        self
+    }
+
+    /// The watched wallet that made the trade, if any.
+    pub fn wallet(&self, trade: &NormalizedTrade) -> Option<&WatchedWallet> {
+        let actor = trade.actor_id.as_deref()?;
+        self.wallets.iter().find(|w| w.matches(actor))
+    }
+
+    /// Add the thresholds of `watched` for `platform`.
+    ///
+    /// Polymarket entries must already be resolved to condition IDs.
+    pub fn with_watched(mut self, platform: Platform, watched: &[WatchedMarket]) -> Self {
+        for entry in watched.iter().filter(|w| w.platform() == platform) {
+            let Some(threshold) = entry.threshold else {
+                continue;
+            };
+            let rule = if let Some(id) = entry.market.as_ref().or(entry.ticker.as_ref()) {
+                MarketMatch::Exact(id.clone())
+            } else if let Some(prefix) = entry.event.as_ref().or(entry.series.as_ref()) {
+                MarketMatch::Prefix(prefix.clone())
+            } else {
+                continue;
+            };
+            self.rules.push((rule, Decimal::from(threshold)));
+        }
+        self
+    }
+
+    pub fn for_trade(&self, trade: &NormalizedTrade) -> Decimal {
+        let market = self.for_market(&trade.market_id);
+
+        match self.wallet(trade) {
+            Some(wallet) => market.min(wallet.threshold.map(Decimal::from).unwrap_or_default()),
+            None => market,
+        }
+    }
+
+    /// The threshold for a trade in `market_id` by a wallet that isn't watched.
+    pub fn for_market(&self, market_id: &str) -> Decimal {
+        self.rules
+            .iter()
+            .filter(|(rule, _)| rule.matches(market_id))
+            .map(|(_, threshold)| *threshold)
+            .min()
+            .unwrap_or(self.default)
+    }
+}